@@ -1,13 +1,20 @@
-#[derive(Debug, Copy, Clone)]
+use super::size::*;
+
+/// Maximum of a `Constraint` that does not limit the size along that axis,
+/// e.g. for the content of a `ScrollElement`. Elements must not grow to an
+/// unbounded maximum.
+pub const UNBOUNDED: f32 = ::std::f32::INFINITY;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Constraint {
-    pub min_width: usize,
-    pub max_width: usize,
-    pub min_height: usize,
-    pub max_height: usize,
+    pub min_width: f32,
+    pub max_width: f32,
+    pub min_height: f32,
+    pub max_height: f32,
 }
 
 impl Constraint {
-    pub fn new(min_width: usize, max_width: usize, min_height: usize, max_height: usize) -> Constraint {
+    pub fn new(min_width: f32, max_width: f32, min_height: f32, max_height: f32) -> Constraint {
         Constraint {
             min_width,
             max_width,
@@ -16,7 +23,7 @@ impl Constraint {
         }
     }
 
-    pub fn fixed(width: usize, height: usize) -> Constraint {
+    pub fn fixed(width: f32, height: f32) -> Constraint {
         Constraint {
             min_width: width,
             max_width: width,
@@ -25,7 +32,102 @@ impl Constraint {
         }
     }
 
+    /// Any size from nothing up to `max_width` and `max_height`.
+    pub fn loose(max_width: f32, max_height: f32) -> Constraint {
+        Constraint::new(0.0, max_width, 0.0, max_height)
+    }
+
+    /// Leaves the size entirely to the element.
+    pub fn unbounded() -> Constraint {
+        Constraint::loose(UNBOUNDED, UNBOUNDED)
+    }
+
+    pub fn has_bounded_width(&self) -> bool {
+        self.max_width < UNBOUNDED
+    }
+
+    pub fn has_bounded_height(&self) -> bool {
+        self.max_height < UNBOUNDED
+    }
+
+    /// Clamps `size` into the constraint.
+    pub fn constrain(&self, size: Size) -> Size {
+        Size::new(
+            size.width.max(self.min_width).min(self.max_width),
+            size.height.max(self.min_height).min(self.max_height),
+        )
+    }
+
+    /// Shrinks the constraint by `width` and `height`, e.g. to make room for
+    /// padding and margin. An unbounded maximum stays unbounded.
+    pub fn deflate(&self, width: f32, height: f32) -> Constraint {
+        Constraint {
+            min_width: (self.min_width - width).max(0.0),
+            max_width: (self.max_width - width).max(0.0),
+            min_height: (self.min_height - height).max(0.0),
+            max_height: (self.max_height - height).max(0.0),
+        }
+    }
+
+    /// Largest size with a `ratio` of width to height within the constraint,
+    /// or `None` if neither axis is bounded. The minimum wins if the ratio
+    /// cannot be kept.
+    pub fn fit_aspect_ratio(&self, ratio: f32) -> Option<Size> {
+        if self.is_fixed() {
+            return Some(Size::new(self.min_width, self.min_height));
+        }
+
+        let (mut width, mut height) = if self.has_bounded_width() {
+            (self.max_width, self.max_width / ratio)
+        } else if self.has_bounded_height() {
+            (self.max_height * ratio, self.max_height)
+        } else {
+            return None;
+        };
+        if height > self.max_height {
+            height = self.max_height;
+            width = height * ratio;
+        }
+        if width < self.min_width {
+            width = self.min_width;
+            height = width / ratio;
+        }
+        if height < self.min_height {
+            height = self.min_height;
+            width = height * ratio;
+        }
+        Some(self.constrain(Size::new(width, height)))
+    }
+
     pub fn is_fixed(&self) -> bool {
         self.min_width == self.max_width && self.min_height == self.max_height
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unbounded_constraints_have_no_aspect_ratio_fit() {
+        assert_eq!(Constraint::unbounded().fit_aspect_ratio(2.0), None);
+    }
+
+    #[test]
+    fn fixed_constraints_keep_their_size() {
+        assert_eq!(Constraint::fixed(10.0, 10.0).fit_aspect_ratio(2.0), Some(Size::new(10.0, 10.0)));
+    }
+
+    #[test]
+    fn the_bounded_axis_decides() {
+        assert_eq!(Constraint::loose(100.0, UNBOUNDED).fit_aspect_ratio(2.0), Some(Size::new(100.0, 50.0)));
+        assert_eq!(Constraint::loose(UNBOUNDED, 100.0).fit_aspect_ratio(2.0), Some(Size::new(200.0, 100.0)));
+        assert_eq!(Constraint::loose(100.0, 20.0).fit_aspect_ratio(2.0), Some(Size::new(40.0, 20.0)));
+    }
+
+    #[test]
+    fn minimums_win_over_the_ratio() {
+        assert_eq!(Constraint::new(0.0, 100.0, 80.0, 200.0).fit_aspect_ratio(2.0), Some(Size::new(100.0, 80.0)));
+        assert_eq!(Constraint::new(60.0, 100.0, 0.0, 20.0).fit_aspect_ratio(2.0), Some(Size::new(60.0, 20.0)));
+    }
+}
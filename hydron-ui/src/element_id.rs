@@ -0,0 +1,27 @@
+/// Handle to an element stored in a `Surface`.
+///
+/// Slots of removed elements are recycled, so the handle carries the
+/// generation of the slot it was issued for. Once the element is removed the
+/// generation of the slot changes and the handle is rejected by the `Surface`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ElementId {
+    index: usize,
+    generation: usize,
+}
+
+impl ElementId {
+    pub(crate) fn new(index: usize, generation: usize) -> ElementId {
+        ElementId {
+            index,
+            generation,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+}
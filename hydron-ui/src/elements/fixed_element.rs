@@ -1,15 +1,18 @@
 use ::layout::*;
 use ::constraint::*;
+use ::element_id::*;
+use ::error::*;
 use ::size::*;
 use ::surface::*;
 
 pub struct FixedElement {
-    width: usize,
-    height: usize,
+    width: f32,
+    height: f32,
 }
 
 impl FixedElement {
-    pub fn new(width: usize, height: usize) -> FixedElement {
+    /// Element of `width` by `height` logical units.
+    pub fn new(width: f32, height: f32) -> FixedElement {
         FixedElement {
             width,
             height,
@@ -18,22 +21,38 @@ impl FixedElement {
 }
 
 impl LayoutElement for FixedElement {
-    fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<usize>, requested: Option<Size>) -> LayoutResult {
-        let constrained_width = self.width.min(constraint.max_width);
-        let constrained_height = self.height.min(constraint.max_height);
-        match children.len() {
-            0 => LayoutResult::Done(Size::new(constrained_width, constrained_height)),
-            1 => {
-                if let Some(size) = requested {
-                    context.position_child(children[0], 0, 0);
-                    LayoutResult::Done(Size::new(constrained_width, constrained_height))
-                } else {
-                    LayoutResult::LayoutChild(children[0], Constraint::new(0, constrained_width, 0, constrained_height))
-                }
-            }
-            _ => {
-                unimplemented!()
-            }
+    #[allow(unused)]
+    fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<ElementId>, requested: Option<Size>) -> Result<LayoutResult, SurfaceError> {
+        let constrained_width = self.width.max(constraint.min_width).min(constraint.max_width);
+        let constrained_height = self.height.max(constraint.min_height).min(constraint.max_height);
+        let ix = context.laid_out_children();
+        if ix < children.len() {
+            return Ok(LayoutResult::LayoutChild(children[ix], Constraint::loose(constrained_width, constrained_height)));
         }
+
+        for child in children.iter() {
+            context.position_child(*child, 0.0, 0.0)?;
+        }
+        Ok(LayoutResult::Done(Size::new(constrained_width, constrained_height)))
+    }
+
+    #[allow(unused)]
+    fn min_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        Ok(self.width)
+    }
+
+    #[allow(unused)]
+    fn max_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        Ok(self.width)
+    }
+
+    #[allow(unused)]
+    fn min_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        Ok(self.height)
+    }
+
+    #[allow(unused)]
+    fn max_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        Ok(self.height)
     }
 }
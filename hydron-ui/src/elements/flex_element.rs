@@ -1,94 +1,585 @@
 use ::layout::*;
 use ::constraint::*;
+use ::element_id::*;
+use ::error::*;
 use ::size::*;
 use ::surface::*;
+use ::style::*;
 
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Axis {
     Horizontal,
     Vertical,
 }
 
 impl Axis {
-    fn major(&self, coords: (usize, usize)) -> usize {
+    pub(crate) fn major(&self, coords: (f32, f32)) -> f32 {
         match self {
             Axis::Horizontal => coords.0,
             Axis::Vertical => coords.1,
         }
     }
 
-    fn minor(&self, coords: (usize, usize)) -> usize {
+    pub(crate) fn minor(&self, coords: (f32, f32)) -> f32 {
         match self {
             Axis::Horizontal => coords.1,
             Axis::Vertical => coords.0,
         }
     }
 
-    fn pack(&self, major: usize, minor: usize) -> (usize, usize) {
+    pub(crate) fn pack(&self, major: f32, minor: f32) -> (f32, f32) {
         match self {
             Axis::Horizontal => (major, minor),
             Axis::Vertical => (minor, major),
         }
     }
+
+    pub(crate) fn other(&self) -> Axis {
+        match self {
+            Axis::Horizontal => Axis::Vertical,
+            Axis::Vertical => Axis::Horizontal,
+        }
+    }
+
+    pub(crate) fn constraint(&self, min_major: f32, max_major: f32, min_minor: f32, max_minor: f32) -> Constraint {
+        match self {
+            Axis::Horizontal => Constraint::new(min_major, max_major, min_minor, max_minor),
+            Axis::Vertical => Constraint::new(min_minor, max_minor, min_major, max_major),
+        }
+    }
+}
+
+enum Phase {
+    Arrange,
+    Stretch,
 }
 
+/// Children of a `FlexElement` that share one line.
+struct Line {
+    start: usize,
+    end: usize,
+    minor: f32,
+    offset: f32,
+}
+
+impl Line {
+    fn new(start: usize, end: usize) -> Line {
+        Line {
+            start,
+            end,
+            minor: 0.0,
+            offset: 0.0,
+        }
+    }
+}
+
+/// Lays out its children in a row or column.
+///
+/// Every child starts out with its `flex_basis`, or its max intrinsic size if
+/// it has none. Unless `wrap` is `Wrap::NoWrap`, children that do not fit
+/// along the major axis are moved to a new line. Free space on a line is then
+/// handed out in proportion to `flex_grow`, missing space is taken away in
+/// proportion to `flex_shrink` weighted by the basis. Space that is left over
+/// is distributed according to `justify_content`, children are placed on the
+/// minor axis of their line according to `align_items` or their own
+/// `align_self`, and lines are distributed according to `align_content`.
+/// In `Direction::Rtl` everything is mirrored horizontally, so a row starts
+/// at the right edge.
 pub struct FlexElement {
     direction: Axis,
+    wrap: Wrap,
+    justify_content: Justify,
+    align_items: Align,
+    align_content: AlignContent,
+    gap: f32,
+    phase: Phase,
     ix: usize,
-    major_per_flex: usize,
-    minor: usize,
+    bases: Vec<f32>,
+    majors: Vec<f32>,
+    lines: Vec<Line>,
+    major: f32,
+    minor: f32,
+    baseline: Option<f32>,
 }
 
 impl FlexElement {
     pub fn new(direction: Axis) -> FlexElement {
         FlexElement {
             direction,
+            wrap: Wrap::NoWrap,
+            justify_content: Justify::Start,
+            align_items: Align::Stretch,
+            align_content: AlignContent::Stretch,
+            gap: 0.0,
+            phase: Phase::Arrange,
             ix: 0,
-            major_per_flex: 0,
-            minor: 0,
+            bases: vec![],
+            majors: vec![],
+            lines: vec![],
+            major: 0.0,
+            minor: 0.0,
+            baseline: None,
         }
     }
-}
 
-impl LayoutElement for FlexElement {
-    fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<usize>, requested: Option<Size>) -> LayoutResult {
-        if let Some(size) = requested {
-            let minor = self.direction.minor((size.width, size.height));
-            self.minor = self.minor.max(minor);
-            self.ix += 1;
+    pub fn with_wrap(mut self, wrap: Wrap) -> FlexElement {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn with_justify_content(mut self, justify_content: Justify) -> FlexElement {
+        self.justify_content = justify_content;
+        self
+    }
+
+    pub fn with_align_items(mut self, align_items: Align) -> FlexElement {
+        self.align_items = align_items;
+        self
+    }
+
+    pub fn with_align_content(mut self, align_content: AlignContent) -> FlexElement {
+        self.align_content = align_content;
+        self
+    }
+
+    /// Space between two children on a line and between two lines, in
+    /// logical units.
+    pub fn with_gap(mut self, gap: f32) -> FlexElement {
+        self.gap = gap;
+        self
+    }
+
+    fn align(&self, context: &LayoutContext, child: ElementId) -> Result<Align, SurfaceError> {
+        Ok(context.get_child_style(child)?.align_self.unwrap_or(self.align_items))
+    }
+
+    /// Whether the minor size of the only line is known before its children
+    /// are laid out.
+    fn definite_minor(&self, min_minor: f32, max_minor: f32) -> bool {
+        self.lines.len() == 1 && min_minor == max_minor
+    }
+
+    /// Whether `child` is first laid out once the minor size of its line is
+    /// known, instead of at its own minor size and then again stretched.
+    fn stretches_later(&self, context: &LayoutContext, child: ElementId, min_minor: f32, max_minor: f32) -> Result<bool, SurfaceError> {
+        Ok(self.align(context, child)? == Align::Stretch && !self.definite_minor(min_minor, max_minor))
+    }
+
+    fn is_horizontal(&self) -> bool {
+        match self.direction {
+            Axis::Horizontal => true,
+            Axis::Vertical => false,
+        }
+    }
 
-            if self.ix == children.len() {
-                let mut major = 0;
-                for child in children {
-                    let child_size = context.get_child_size(*child);
-                    let child_major_size = self.direction.major((child_size.width, child_size.height));
-                    let padding = if self.major_per_flex > child_major_size {
-                        (self.major_per_flex - child_major_size) / 2
-                    } else {
-                        0
-                    };
-                    let (x, y) = self.direction.pack(major + padding, 0);
-                    context.position_child(*child, x, y);
-                    major += self.major_per_flex;
+    fn wraps(&self) -> bool {
+        match self.wrap {
+            Wrap::NoWrap => false,
+            Wrap::Wrap | Wrap::WrapReverse => true,
+        }
+    }
+
+    /// Size of `child` along the major axis before free space is handed out,
+    /// with percentages taken of `max_major`.
+    fn basis(&self, context: &LayoutContext, child: ElementId, max_major: f32, max_minor: f32) -> Result<f32, SurfaceError> {
+        let style = context.get_child_style(child)?;
+        let units = context.get_child_units(child)?;
+        match style.flex_basis.and_then(|basis| basis.resolve(max_major, &units)) {
+            Some(basis) => {
+                let (margin, border, padding) = context.get_child_insets(child, max_major)?;
+                let insets = margin + border + padding;
+                Ok(basis + self.direction.major((insets.horizontal(), insets.vertical())))
+            },
+            None => child_intrinsic(context, child, self.direction, true, max_minor),
+        }
+    }
+
+    /// Min or max content size of the element along `axis`, given `cross`
+    /// along the other axis.
+    fn intrinsic(&self, axis: Axis, max: bool, cross: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        if children.is_empty() {
+            return Ok(0.0);
+        }
+
+        if axis == self.direction {
+            let mut sizes = vec![];
+            for child in children.iter() {
+                let size = if max { self.basis(context, *child, UNBOUNDED, cross)? } else { child_intrinsic(context, *child, axis, false, cross)? };
+                sizes.push(size);
+            }
+            let gaps = self.gap * (children.len() - 1) as f32;
+            return Ok(if self.wraps() && !max {
+                sizes.iter().fold(0.0, |a: f32, b| a.max(*b))
+            } else {
+                sizes.iter().sum::<f32>() + gaps
+            });
+        }
+
+        // lines are broken as if the element was `cross` long
+        let mut bases = vec![];
+        for child in children.iter() {
+            let basis = self.basis(context, *child, UNBOUNDED, UNBOUNDED)?;
+            bases.push(basis);
+        }
+        let lines = break_lines(&bases, cross, self.gap, self.wraps());
+        let mut total = self.gap * (lines.len() - 1) as f32;
+        for (start, end) in lines {
+            let mut line: f32 = 0.0;
+            for ix in start..end {
+                line = line.max(child_intrinsic(context, children[ix], axis, max, bases[ix])?);
+            }
+            total += line;
+        }
+        Ok(total)
+    }
+
+    fn measure(&mut self, context: &LayoutContext, children: &Vec<ElementId>, max_major: f32, max_minor: f32) -> Result<(), SurfaceError> {
+        self.bases.clear();
+        for child in children.iter() {
+            let basis = self.basis(context, *child, max_major, max_minor)?;
+            self.bases.push(basis);
+        }
+
+        // without a limit the element is as long as its children
+        self.major = if max_major == UNBOUNDED {
+            self.bases.iter().sum::<f32>() + self.gap * (self.bases.len() - 1) as f32
+        } else {
+            max_major
+        };
+
+        let wraps = self.wraps();
+        self.lines = break_lines(&self.bases, self.major, self.gap, wraps).into_iter()
+            .map(|(start, end)| Line::new(start, end))
+            .collect();
+        let major = self.major;
+        self.resolve_flexible_lengths(context, children, major)
+    }
+
+    fn resolve_flexible_lengths(&mut self, context: &LayoutContext, children: &Vec<ElementId>, max_major: f32) -> Result<(), SurfaceError> {
+        self.majors.clear();
+        for line in self.lines.iter() {
+            let bases = &self.bases[line.start..line.end];
+            let available = (max_major - self.gap * (bases.len() - 1) as f32).max(0.0);
+            let used: f32 = bases.iter().sum();
+
+            let mut weights = vec![];
+            for (child, basis) in children[line.start..line.end].iter().zip(bases.iter()) {
+                let style = context.get_child_style(*child)?;
+                if used < available {
+                    weights.push(style.flex_grow.max(0.0));
+                } else {
+                    weights.push(style.flex_shrink.max(0.0) * *basis);
+                }
+            }
+
+            let total_weight: f32 = weights.iter().sum();
+            if total_weight <= 0.0 {
+                self.majors.extend(bases.iter());
+                continue;
+            }
+
+            let free = available - used;
+            for (basis, weight) in bases.iter().zip(weights.iter()) {
+                self.majors.push((basis + free * weight / total_weight).max(0.0));
+            }
+        }
+        Ok(())
+    }
+
+    /// Determines the minor size of every line and of the element itself,
+    /// and where the lines go along the minor axis. Children that are not laid
+    /// out yet count with their max content size.
+    fn resolve_lines(&mut self, context: &LayoutContext, children: &Vec<ElementId>, min_minor: f32, max_minor: f32) -> Result<(), SurfaceError> {
+        let single_line = self.lines.len() == 1;
+        for ix in 0..self.lines.len() {
+            let mut minor = if single_line { min_minor } else { 0.0 };
+            let mut ascent: f32 = 0.0;
+            let mut descent: f32 = 0.0;
+            for child_ix in self.lines[ix].start..self.lines[ix].end {
+                let child = &children[child_ix];
+                let child_minor = if self.stretches_later(context, *child, min_minor, max_minor)? {
+                    child_intrinsic(context, *child, self.direction.other(), true, self.majors[child_ix])?
+                } else {
+                    let child_size = context.get_child_size(*child)?;
+                    self.direction.minor((child_size.width, child_size.height))
+                };
+                match self.align(context, *child)? {
+                    Align::Baseline if self.is_horizontal() => {
+                        let baseline = context.get_child_baseline(*child)?;
+                        ascent = ascent.max(baseline);
+                        descent = descent.max(child_minor - baseline);
+                    },
+                    _ => minor = minor.max(child_minor),
                 }
-                let max_major = self.direction.major((constraint.max_width, constraint.max_height));
-                let (w, h) = self.direction.pack(max_major, self.minor);
-                return LayoutResult::Done(Size::new(w, h));
             }
+            self.lines[ix].minor = minor.max(ascent + descent);
+        }
+
+        let used = self.lines.iter().map(|line| line.minor).sum::<f32>() + self.gap * (self.lines.len() - 1) as f32;
+        self.minor = used.max(min_minor).min(max_minor);
+        let free = (self.minor - used).max(0.0);
+
+        let (start, between) = match self.align_content {
+            AlignContent::Stretch => {
+                let count = self.lines.len() as f32;
+                for line in self.lines.iter_mut() {
+                    line.minor += free / count;
+                }
+                (0.0, self.gap)
+            },
+            AlignContent::Start => distribute(Justify::Start, free, self.lines.len(), self.gap),
+            AlignContent::End => distribute(Justify::End, free, self.lines.len(), self.gap),
+            AlignContent::Center => distribute(Justify::Center, free, self.lines.len(), self.gap),
+            AlignContent::SpaceBetween => distribute(Justify::SpaceBetween, free, self.lines.len(), self.gap),
+            AlignContent::SpaceAround => distribute(Justify::SpaceAround, free, self.lines.len(), self.gap),
+            AlignContent::SpaceEvenly => distribute(Justify::SpaceEvenly, free, self.lines.len(), self.gap),
+        };
+
+        let mut offset = start;
+        for line in self.lines.iter_mut() {
+            line.offset = match self.wrap {
+                Wrap::WrapReverse => (self.minor - offset - line.minor).max(0.0),
+                Wrap::NoWrap | Wrap::Wrap => offset,
+            };
+            offset += line.minor + between;
+        }
+        Ok(())
+    }
+
+    fn line_of(&self, ix: usize) -> &Line {
+        self.lines.iter().find(|line| ix < line.end).unwrap()
+    }
+}
+
+/// Offset of the first item and the space between two items when `free`
+/// space is distributed between `count` items that are `gap` apart.
+fn distribute(justify: Justify, free: f32, count: usize, gap: f32) -> (f32, f32) {
+    let count = count as f32;
+    match justify {
+        Justify::Start => (0.0, gap),
+        Justify::End => (free, gap),
+        Justify::Center => (free / 2.0, gap),
+        Justify::SpaceBetween if count > 1.0 => (0.0, gap + free / (count - 1.0)),
+        Justify::SpaceBetween => (0.0, gap),
+        Justify::SpaceAround => (free / (2.0 * count), gap + free / count),
+        Justify::SpaceEvenly => (free / (count + 1.0), gap + free / (count + 1.0)),
+    }
+}
+
+/// Splits items of the given sizes into lines that fit into `max_major`,
+/// returned as ranges of item indices. There is always at least one line.
+fn break_lines(sizes: &Vec<f32>, max_major: f32, gap: f32, wraps: bool) -> Vec<(usize, usize)> {
+    let mut lines = vec![];
+    let mut start = 0;
+    let mut used = 0.0;
+    for (ix, size) in sizes.iter().enumerate() {
+        if wraps && ix > start && used + gap + size > max_major {
+            lines.push((start, ix));
+            start = ix;
+            used = 0.0;
+        }
+        if ix > start {
+            used += gap;
+        }
+        used += size;
+    }
+    lines.push((start, sizes.len()));
+    lines
+}
+
+/// Min or max content size of `child` along `axis`, given `cross` along the
+/// other axis.
+fn child_intrinsic(context: &LayoutContext, child: ElementId, axis: Axis, max: bool, cross: f32) -> Result<f32, SurfaceError> {
+    match (axis, max) {
+        (Axis::Horizontal, false) => context.get_child_min_intrinsic_width(child, cross),
+        (Axis::Horizontal, true) => context.get_child_max_intrinsic_width(child, cross),
+        (Axis::Vertical, false) => context.get_child_min_intrinsic_height(child, cross),
+        (Axis::Vertical, true) => context.get_child_max_intrinsic_height(child, cross),
+    }
+}
+
+impl LayoutElement for FlexElement {
+    fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<ElementId>, requested: Option<Size>) -> Result<LayoutResult, SurfaceError> {
+        let min_minor = self.direction.minor((constraint.min_width, constraint.min_height));
+        let max_minor = self.direction.minor((constraint.max_width, constraint.max_height));
+        let max_major = self.direction.major((constraint.max_width, constraint.max_height));
+
+        if requested.is_some() {
+            self.ix += 1;
         } else {
+            self.baseline = None;
             if children.is_empty() {
-                return LayoutResult::Done(Size::new(constraint.min_width, constraint.min_height));
+                return Ok(LayoutResult::Done(Size::new(constraint.min_width, constraint.min_height)));
             }
 
+            self.measure(context, children, max_major, max_minor)?;
+            self.phase = Phase::Arrange;
             self.ix = 0;
-            self.minor = self.direction.minor((constraint.min_width, constraint.min_height));
-            let max_major = self.direction.major((constraint.max_width, constraint.max_height));
-            self.major_per_flex = max_major / children.len();
         }
 
-        let child_constraint = match self.direction {
-            Axis::Horizontal => Constraint::new(self.major_per_flex, self.major_per_flex, constraint.min_height, constraint.max_height),
-            Axis::Vertical => Constraint::new(constraint.min_width, constraint.max_width, self.major_per_flex, self.major_per_flex),
-        };
-        LayoutResult::LayoutChild(children[self.ix], child_constraint)
+        if let Phase::Arrange = self.phase {
+            while self.ix < children.len() {
+                let child = children[self.ix];
+                if self.stretches_later(context, child, min_minor, max_minor)? {
+                    self.ix += 1;
+                    continue;
+                }
+                let major = self.majors[self.ix];
+                let child_constraint = match self.align(context, child)? {
+                    Align::Stretch => self.direction.constraint(major, major, max_minor, max_minor),
+                    _ => self.direction.constraint(major, major, 0.0, max_minor),
+                };
+                return Ok(LayoutResult::LayoutChild(child, child_constraint));
+            }
+
+            self.resolve_lines(context, children, min_minor, max_minor)?;
+            self.phase = Phase::Stretch;
+            self.ix = 0;
+        }
+
+        // stretched children get their only layout at the minor size of the
+        // line
+        while self.ix < children.len() {
+            let child = children[self.ix];
+            if self.stretches_later(context, child, min_minor, max_minor)? {
+                let major = self.majors[self.ix];
+                let line_minor = self.line_of(self.ix).minor;
+                return Ok(LayoutResult::LayoutChild(child, self.direction.constraint(major, major, line_minor, line_minor)));
+            }
+            self.ix += 1;
+        }
+
+        let (width, height) = self.direction.pack(self.major, self.minor);
+        let direction = context.get_direction();
+        for line in self.lines.iter() {
+            let line_children = &children[line.start..line.end];
+
+            let mut used = 0.0;
+            let mut max_baseline: f32 = 0.0;
+            for child in line_children.iter() {
+                let child_size = context.get_child_size(*child)?;
+                used += self.direction.major((child_size.width, child_size.height));
+                if let Align::Baseline = self.align(context, *child)? {
+                    max_baseline = max_baseline.max(context.get_child_baseline(*child)?);
+                }
+            }
+
+            let gaps = self.gap * (line_children.len() - 1) as f32;
+            let free = (self.major - used - gaps).max(0.0);
+            let (start, between) = distribute(self.justify_content, free, line_children.len(), self.gap);
+
+            let mut major = start;
+            for child in line_children.iter() {
+                let child_size = context.get_child_size(*child)?;
+                let child_minor = self.direction.minor((child_size.width, child_size.height));
+                let minor = line.offset + match self.align(context, *child)? {
+                    Align::Start | Align::Stretch => 0.0,
+                    Align::End => (line.minor - child_minor).max(0.0),
+                    Align::Center => (line.minor - child_minor).max(0.0) / 2.0,
+                    Align::Baseline if self.is_horizontal() => max_baseline - context.get_child_baseline(*child)?,
+                    Align::Baseline => 0.0,
+                };
+
+                if self.baseline.is_none() && self.is_horizontal() {
+                    self.baseline = Some(minor + context.get_child_baseline(*child)?);
+                }
+
+                let (x, y) = self.direction.pack(major, minor);
+                context.position_child(*child, direction.place(x, child_size.width, width), y)?;
+                major += self.direction.major((child_size.width, child_size.height)) + between;
+            }
+        }
+
+        Ok(LayoutResult::Done(Size::new(width, height)))
+    }
+
+    fn baseline(&self) -> Option<f32> {
+        self.baseline
+    }
+
+    fn min_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        self.intrinsic(Axis::Horizontal, false, height, context, children)
+    }
+
+    fn max_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        self.intrinsic(Axis::Horizontal, true, height, context, children)
+    }
+
+    fn min_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        self.intrinsic(Axis::Vertical, false, width, context, children)
+    }
+
+    fn max_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        self.intrinsic(Axis::Vertical, true, width, context, children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::elements::*;
+    use ::std::cell::Cell;
+    use ::std::rc::Rc;
+
+    /// Element 10 high that remembers how it was laid out.
+    struct Counted {
+        layouts: Rc<Cell<usize>>,
+        constraint: Rc<Cell<Option<Constraint>>>,
+    }
+
+    impl LayoutElement for Counted {
+        #[allow(unused)]
+        fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<ElementId>, requested: Option<Size>) -> Result<LayoutResult, SurfaceError> {
+            self.layouts.set(self.layouts.get() + 1);
+            self.constraint.set(Some(constraint));
+            Ok(LayoutResult::Done(constraint.constrain(Size::new(0.0, 10.0))))
+        }
+
+        #[allow(unused)]
+        fn max_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+            Ok(10.0)
+        }
+    }
+
+    #[test]
+    fn stretched_children_are_laid_out_once() {
+        let mut surface = Surface::new(Size::new(100.0, 100.0));
+        let root = surface.insert(Box::new(StackElement::new()));
+        surface.set_root(root).unwrap();
+        let flex = surface.insert(Box::new(FlexElement::new(Axis::Horizontal)));
+        surface.set_parent(flex, root).unwrap();
+        let layouts = Rc::new(Cell::new(0));
+        let constraint = Rc::new(Cell::new(None));
+        let stretched = surface.insert(Box::new(Counted { layouts: layouts.clone(), constraint: constraint.clone() }));
+        surface.set_parent(stretched, flex).unwrap();
+        let tall = surface.insert(Box::new(FixedElement::new(20.0, 30.0)));
+        surface.set_parent(tall, flex).unwrap();
+
+        surface.layout().unwrap();
+        assert_eq!(layouts.get(), 1);
+        assert_eq!(constraint.get(), Some(Constraint::fixed(0.0, 30.0)));
+
+        for width in 1..3 {
+            surface.resize(Size::new(100.0 + width as f32, 100.0));
+            surface.layout().unwrap();
+        }
+        assert_eq!(layouts.get(), 1);
+    }
+
+    #[test]
+    fn stretched_children_grow_lines_by_their_content() {
+        let mut surface = Surface::new(Size::new(100.0, 100.0));
+        let root = surface.insert(Box::new(StackElement::new()));
+        surface.set_root(root).unwrap();
+        let flex = surface.insert(Box::new(FlexElement::new(Axis::Horizontal)));
+        surface.set_parent(flex, root).unwrap();
+        let constraint = Rc::new(Cell::new(None));
+        let stretched = surface.insert(Box::new(Counted { layouts: Rc::new(Cell::new(0)), constraint: constraint.clone() }));
+        surface.set_parent(stretched, flex).unwrap();
+        let short = surface.insert(Box::new(FixedElement::new(20.0, 5.0)));
+        surface.set_parent(short, flex).unwrap();
+
+        surface.layout().unwrap();
+        assert_eq!(constraint.get(), Some(Constraint::fixed(0.0, 10.0)));
     }
 }
@@ -0,0 +1,481 @@
+use ::layout::*;
+use ::constraint::*;
+use ::element_id::*;
+use ::error::*;
+use ::size::*;
+use ::surface::*;
+use ::style::*;
+
+/// Lower or upper limit for the size of a grid track.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TrackBreadth {
+    /// Logical units, like `Length::Dp`.
+    Dp(f32),
+    /// Share of the space left after all other tracks are sized. Only
+    /// meaningful as the maximum of a track.
+    Fr(f32),
+    /// Large enough for the children in the track.
+    Auto,
+}
+
+/// Row or column definition of a `GridElement`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Track {
+    pub min: TrackBreadth,
+    pub max: TrackBreadth,
+}
+
+impl Track {
+    /// Track of a fixed size in logical units.
+    pub fn dp(dp: f32) -> Track {
+        Track::min_max(TrackBreadth::Dp(dp), TrackBreadth::Dp(dp))
+    }
+
+    pub fn fr(fr: f32) -> Track {
+        Track::min_max(TrackBreadth::Auto, TrackBreadth::Fr(fr))
+    }
+
+    pub fn auto() -> Track {
+        Track::min_max(TrackBreadth::Auto, TrackBreadth::Auto)
+    }
+
+    pub fn min_max(min: TrackBreadth, max: TrackBreadth) -> Track {
+        Track {
+            min,
+            max,
+        }
+    }
+}
+
+/// Cells covered by a child.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Area {
+    row: GridPlacement,
+    column: GridPlacement,
+}
+
+/// Lays out its children in rows and columns.
+///
+/// Children are placed by their `grid_row` and `grid_column` style, children
+/// without a placement fill the next free cells row by row. Rows that are not
+/// defined are added as `Track::auto()`. Tracks are first sized to their
+/// fixed size or to their content, using the intrinsic widths of the children
+/// for columns and their intrinsic heights at the width of their columns for
+/// rows, then space that is left is handed out to `Fr` tracks, or without a
+/// limit they are sized to their content. Children fill their cells, unless
+/// `align_self` places them vertically inside the cell, and are laid out
+/// once. In `Direction::Rtl` the first column is on the right.
+pub struct GridElement {
+    columns: Vec<Track>,
+    rows: Vec<Track>,
+    column_gap: f32,
+    row_gap: f32,
+    ix: usize,
+    areas: Vec<Area>,
+    column_sizes: Vec<f32>,
+    row_sizes: Vec<f32>,
+}
+
+impl GridElement {
+    pub fn new(columns: Vec<Track>, rows: Vec<Track>) -> GridElement {
+        GridElement {
+            columns,
+            rows,
+            column_gap: 0.0,
+            row_gap: 0.0,
+            ix: 0,
+            areas: vec![],
+            column_sizes: vec![],
+            row_sizes: vec![],
+        }
+    }
+
+    /// Space between two columns in logical units.
+    pub fn with_column_gap(mut self, column_gap: f32) -> GridElement {
+        self.column_gap = column_gap;
+        self
+    }
+
+    /// Space between two rows in logical units.
+    pub fn with_row_gap(mut self, row_gap: f32) -> GridElement {
+        self.row_gap = row_gap;
+        self
+    }
+
+    fn place_children(&self, context: &LayoutContext, children: &Vec<ElementId>) -> Result<Vec<Area>, SurfaceError> {
+        let mut placements = vec![];
+        for child in children.iter() {
+            let style = context.get_child_style(*child)?;
+            placements.push((style.grid_row, style.grid_column));
+        }
+        Ok(place_areas(&placements, self.columns.len().max(1)))
+    }
+
+    /// Sizes the columns for `areas` from the intrinsic widths of the
+    /// children, `max` selecting the max intrinsic over the min intrinsic
+    /// width as the largest size of auto tracks.
+    fn size_columns(&self, context: &LayoutContext, children: &Vec<ElementId>, areas: &Vec<Area>, max: bool, available: f32) -> Result<Vec<f32>, SurfaceError> {
+        let mut items = vec![];
+        for (child, area) in children.iter().zip(areas.iter()) {
+            let min_width = context.get_child_min_intrinsic_width(*child, UNBOUNDED)?;
+            let max_width = if max { context.get_child_max_intrinsic_width(*child, UNBOUNDED)? } else { min_width };
+            items.push((area.column, min_width, max_width));
+        }
+        let column_count = areas.iter()
+            .map(|area| area.column.start() + area.column.span())
+            .fold(self.columns.len(), |a, b| a.max(b));
+        Ok(size_tracks(&self.columns, column_count, &items, available, self.column_gap))
+    }
+
+    /// Intrinsic height of the grid when it is `width` wide.
+    fn intrinsic_height(&self, max: bool, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        let areas = self.place_children(context, children)?;
+        let column_sizes = self.size_columns(context, children, &areas, true, width)?;
+
+        let mut items = vec![];
+        for (child, area) in children.iter().zip(areas.iter()) {
+            let column_width = span_size(&column_sizes, area.column, self.column_gap);
+            let min_height = context.get_child_min_intrinsic_height(*child, column_width)?;
+            let max_height = if max { context.get_child_max_intrinsic_height(*child, column_width)? } else { min_height };
+            items.push((area.row, min_height, max_height));
+        }
+        let row_count = areas.iter()
+            .map(|area| area.row.start() + area.row.span())
+            .fold(self.rows.len(), |a, b| a.max(b));
+        Ok(total_size(&size_tracks(&self.rows, row_count, &items, UNBOUNDED, self.row_gap), self.row_gap))
+    }
+
+    fn column_width(&self, area: &Area) -> f32 {
+        span_size(&self.column_sizes, area.column, self.column_gap)
+    }
+
+    fn row_height(&self, area: &Area) -> f32 {
+        span_size(&self.row_sizes, area.row, self.row_gap)
+    }
+}
+
+/// Cells of children with the `(row, column)` placements, in a grid of
+/// `column_count` columns. Children without a placement fill the next free
+/// cells row by row, children with only a row or a column take the first
+/// free cells in it.
+fn place_areas(placements: &Vec<(Option<GridPlacement>, Option<GridPlacement>)>, column_count: usize) -> Vec<Area> {
+    let mut occupied: Vec<Vec<bool>> = vec![];
+    let mut cursor = (0, 0);
+
+    let mut areas = vec![];
+    for &(row, column) in placements.iter() {
+        let area = match (row, column) {
+            (Some(row), Some(column)) => Area { row, column },
+            (row, column) => {
+                let row_span = row.map(|row| row.span()).unwrap_or(1);
+                let column_span = column.map(|column| column.span()).unwrap_or(1).min(column_count);
+                let (mut r, mut c) = match (row, column) {
+                    (Some(row), _) => (row.start(), 0),
+                    (None, Some(column)) => (0, column.start()),
+                    (None, None) => cursor,
+                };
+                while !is_free(&occupied, r, c, row_span, column_span) || (row.is_none() && column.is_none() && c + column_span > column_count) {
+                    match (row, column) {
+                        (Some(_), _) => c += 1,
+                        (None, Some(_)) => r += 1,
+                        (None, None) if c + column_span >= column_count => {
+                            r += 1;
+                            c = 0;
+                        },
+                        (None, None) => c += 1,
+                    }
+                }
+                if row.is_none() && column.is_none() {
+                    cursor = (r, c + column_span);
+                }
+                Area {
+                    row: GridPlacement::new(r, row_span),
+                    column: GridPlacement::new(c, column_span),
+                }
+            },
+        };
+
+        for r in area.row.start()..area.row.start() + area.row.span() {
+            while occupied.len() <= r {
+                occupied.push(vec![]);
+            }
+            for c in area.column.start()..area.column.start() + area.column.span() {
+                while occupied[r].len() <= c {
+                    occupied[r].push(false);
+                }
+                occupied[r][c] = true;
+            }
+        }
+        areas.push(area);
+    }
+    areas
+}
+
+fn is_free(occupied: &Vec<Vec<bool>>, row: usize, column: usize, row_span: usize, column_span: usize) -> bool {
+    for r in row..row + row_span {
+        for c in column..column + column_span {
+            if occupied.get(r).and_then(|cells| cells.get(c)).cloned().unwrap_or(false) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn span_size(sizes: &Vec<f32>, placement: GridPlacement, gap: f32) -> f32 {
+    let tracks = &sizes[placement.start()..placement.start() + placement.span()];
+    tracks.iter().sum::<f32>() + gap * (tracks.len() - 1) as f32
+}
+
+fn offset(sizes: &Vec<f32>, track: usize, gap: f32) -> f32 {
+    sizes[..track].iter().sum::<f32>() + gap * track as f32
+}
+
+fn total_size(sizes: &Vec<f32>, gap: f32) -> f32 {
+    sizes.iter().sum::<f32>() + gap * sizes.len().saturating_sub(1) as f32
+}
+
+/// Sizes `count` tracks so that every child fits into the tracks it spans
+/// and distributes what is left of `available` to flexible tracks. Items
+/// carry the min and max content size of a child.
+///
+/// Content sized minimums take the min content size, auto maximums the max
+/// content size of the children in the track.
+fn size_tracks(tracks: &Vec<Track>, count: usize, items: &Vec<(GridPlacement, f32, f32)>, available: f32, gap: f32) -> Vec<f32> {
+    let track = |ix: usize| tracks.get(ix).cloned().unwrap_or(Track::auto());
+
+    let mut min_content: Vec<f32> = vec![0.0; count];
+    let mut max_content: Vec<f32> = vec![0.0; count];
+    for &(placement, min, max) in items.iter() {
+        if placement.span() == 1 {
+            min_content[placement.start()] = min_content[placement.start()].max(min);
+            max_content[placement.start()] = max_content[placement.start()].max(max);
+        }
+    }
+
+    let mut sizes: Vec<f32> = (0..count).map(|ix| {
+        let track = track(ix);
+        let min = match track.min {
+            TrackBreadth::Dp(dp) => dp,
+            TrackBreadth::Fr(_) | TrackBreadth::Auto => min_content[ix],
+        };
+        match track.max {
+            TrackBreadth::Dp(dp) => dp.max(min),
+            TrackBreadth::Fr(_) if available == UNBOUNDED => min.max(max_content[ix]),
+            TrackBreadth::Fr(_) => min,
+            TrackBreadth::Auto => min.max(max_content[ix]),
+        }
+    }).collect();
+
+    // children spanning several tracks grow the content sized tracks they span
+    for &(placement, _, size) in items.iter() {
+        if placement.span() < 2 {
+            continue;
+        }
+        let range = placement.start()..placement.start() + placement.span();
+        let current = span_size(&sizes, placement, gap);
+        if size <= current {
+            continue;
+        }
+        let growable: Vec<usize> = range.filter(|ix| track(*ix).max == TrackBreadth::Auto).collect();
+        let count = growable.len() as f32;
+        let missing = size - current;
+        for ix in growable {
+            sizes[ix] += missing / count;
+        }
+    }
+
+    let total_fr: f32 = (0..count).filter_map(|ix| match track(ix).max {
+        TrackBreadth::Fr(fr) => Some(fr.max(0.0)),
+        _ => None,
+    }).sum();
+
+    if total_fr > 0.0 && available != UNBOUNDED {
+        let fixed: f32 = (0..count).filter(|ix| match track(*ix).max {
+            TrackBreadth::Fr(_) => false,
+            _ => true,
+        }).map(|ix| sizes[ix]).sum();
+        let free = (available - fixed - gap * count.saturating_sub(1) as f32).max(0.0);
+        for ix in 0..count {
+            if let TrackBreadth::Fr(fr) = track(ix).max {
+                sizes[ix] = sizes[ix].max(free * fr.max(0.0) / total_fr);
+            }
+        }
+    }
+
+    sizes
+}
+
+impl LayoutElement for GridElement {
+    fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<ElementId>, requested: Option<Size>) -> Result<LayoutResult, SurfaceError> {
+        if requested.is_some() {
+            self.ix += 1;
+        } else {
+            self.areas = self.place_children(context, children)?;
+            self.column_sizes = self.size_columns(context, children, &self.areas, true, constraint.max_width)?;
+
+            // rows are sized before any child is laid out, so every child
+            // is laid out once with the size of its cells
+            let mut items = vec![];
+            for (child, area) in children.iter().zip(self.areas.iter()) {
+                let height = context.get_child_max_intrinsic_height(*child, self.column_width(area))?;
+                items.push((area.row, height, height));
+            }
+            let row_count = self.areas.iter()
+                .map(|area| area.row.start() + area.row.span())
+                .fold(self.rows.len(), |a, b| a.max(b));
+            self.row_sizes = size_tracks(&self.rows, row_count, &items, constraint.max_height, self.row_gap);
+            self.ix = 0;
+        }
+
+        if self.ix < children.len() {
+            let area = self.areas[self.ix];
+            let width = self.column_width(&area);
+            let height = self.row_height(&area);
+            let child_constraint = match context.get_child_style(children[self.ix])?.align_self {
+                None | Some(Align::Stretch) => Constraint::fixed(width, height),
+                Some(_) => Constraint::new(width, width, 0.0, height),
+            };
+            return Ok(LayoutResult::LayoutChild(children[self.ix], child_constraint));
+        }
+
+        let width = total_size(&self.column_sizes, self.column_gap);
+        let height = total_size(&self.row_sizes, self.row_gap);
+        let size = constraint.constrain(Size::new(width, height));
+        for (child, area) in children.iter().zip(self.areas.iter()) {
+            let child_size = context.get_child_size(*child)?;
+            let child_height = child_size.height;
+            let height = self.row_height(area);
+            let y = match context.get_child_style(*child)?.align_self {
+                Some(Align::End) => (height - child_height).max(0.0),
+                Some(Align::Center) => (height - child_height).max(0.0) / 2.0,
+                _ => 0.0,
+            };
+            let x = offset(&self.column_sizes, area.column.start(), self.column_gap);
+            let x = context.get_direction().place(x, child_size.width, size.width);
+            let y = y + offset(&self.row_sizes, area.row.start(), self.row_gap);
+            context.position_child(*child, x, y)?;
+        }
+
+        Ok(LayoutResult::Done(size))
+    }
+
+    #[allow(unused)]
+    fn min_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        let areas = self.place_children(context, children)?;
+        Ok(total_size(&self.size_columns(context, children, &areas, false, UNBOUNDED)?, self.column_gap))
+    }
+
+    #[allow(unused)]
+    fn max_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        let areas = self.place_children(context, children)?;
+        Ok(total_size(&self.size_columns(context, children, &areas, true, UNBOUNDED)?, self.column_gap))
+    }
+
+    fn min_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        self.intrinsic_height(false, width, context, children)
+    }
+
+    fn max_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        self.intrinsic_height(true, width, context, children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::cell::Cell;
+    use ::std::rc::Rc;
+
+    /// Element 10 high that counts how often it is laid out.
+    struct Counted {
+        layouts: Rc<Cell<usize>>,
+    }
+
+    impl LayoutElement for Counted {
+        #[allow(unused)]
+        fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<ElementId>, requested: Option<Size>) -> Result<LayoutResult, SurfaceError> {
+            self.layouts.set(self.layouts.get() + 1);
+            Ok(LayoutResult::Done(constraint.constrain(Size::new(0.0, 10.0))))
+        }
+
+        #[allow(unused)]
+        fn min_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+            Ok(10.0)
+        }
+
+        #[allow(unused)]
+        fn max_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+            Ok(10.0)
+        }
+    }
+
+    fn area(row: usize, column: usize, column_span: usize) -> Area {
+        Area {
+            row: GridPlacement::at(row),
+            column: GridPlacement::new(column, column_span),
+        }
+    }
+
+    #[test]
+    fn auto_placement_fills_rows() {
+        let areas = place_areas(&vec![(None, None); 3], 2);
+        assert_eq!(areas, vec![area(0, 0, 1), area(0, 1, 1), area(1, 0, 1)]);
+    }
+
+    #[test]
+    fn auto_placement_skips_placed_cells() {
+        let placed = (Some(GridPlacement::at(0)), Some(GridPlacement::at(0)));
+        let areas = place_areas(&vec![placed, (None, None), (None, None)], 2);
+        assert_eq!(areas, vec![area(0, 0, 1), area(0, 1, 1), area(1, 0, 1)]);
+    }
+
+    #[test]
+    fn placement_with_a_row_span_avoids_taken_cells() {
+        let taken = (Some(GridPlacement::at(1)), Some(GridPlacement::at(0)));
+        let tall = (Some(GridPlacement::new(0, 2)), None);
+        let areas = place_areas(&vec![taken, tall], 2);
+        assert_eq!(areas[1], Area { row: GridPlacement::new(0, 2), column: GridPlacement::at(1) });
+    }
+
+    #[test]
+    fn column_spans_are_clamped_to_the_columns() {
+        let areas = place_areas(&vec![(None, Some(GridPlacement::new(0, 5)))], 2);
+        assert_eq!(areas, vec![area(0, 0, 2)]);
+    }
+
+    #[test]
+    fn placement_in_a_row_or_column_takes_the_first_free_cell() {
+        let first = (Some(GridPlacement::at(1)), Some(GridPlacement::at(0)));
+        let in_row = (Some(GridPlacement::at(1)), None);
+        let in_column = (None, Some(GridPlacement::at(0)));
+        let areas = place_areas(&vec![first, in_row, in_column, in_column], 2);
+        assert_eq!(areas, vec![area(1, 0, 1), area(1, 1, 1), area(0, 0, 1), area(2, 0, 1)]);
+    }
+
+    #[test]
+    fn zero_spans_cover_one_track() {
+        assert_eq!(GridPlacement::new(1, 0).span(), 1);
+        assert_eq!(span_size(&vec![10.0, 20.0], GridPlacement::new(1, 0), 5.0), 20.0);
+    }
+
+    #[test]
+    fn children_are_laid_out_once() {
+        let mut surface = Surface::new(Size::new(100.0, 100.0));
+        let grid = surface.insert(Box::new(GridElement::new(vec![Track::dp(20.0), Track::dp(30.0)], vec![])));
+        surface.set_root(grid).unwrap();
+        let layouts = Rc::new(Cell::new(0));
+        for _ in 0..3 {
+            let child = surface.insert(Box::new(Counted { layouts: layouts.clone() }));
+            surface.set_parent(child, grid).unwrap();
+        }
+
+        surface.layout().unwrap();
+        assert_eq!(layouts.get(), 3);
+
+        // clean children keep their cells and are not laid out again
+        surface.resize(Size::new(200.0, 100.0));
+        surface.layout().unwrap();
+        assert_eq!(layouts.get(), 3);
+    }
+}
@@ -0,0 +1,296 @@
+use ::layout::*;
+use ::constraint::*;
+use ::element_id::*;
+use ::error::*;
+use ::size::*;
+use ::surface::*;
+use ::style::*;
+use ::rect::*;
+use ::std::ops::Range;
+use super::flex_element::Axis;
+use super::scroll_element::*;
+
+/// Rows shown by a `ListElement`.
+pub trait ListDataSource {
+    fn row_count(&self) -> usize;
+
+    /// Height of `row` before it was built and laid out. Rows are measured
+    /// once they are visible, so this may be an estimate.
+    fn row_height(&self, row: usize) -> f32;
+
+    /// Inserts the elements for `row` into the surface and returns the top
+    /// most one. It is removed with `Surface::remove` once the row scrolls
+    /// out of view.
+    fn build_row(&mut self, row: usize, surface: &mut Surface) -> Result<ElementId, SurfaceError>;
+}
+
+/// Vertically scrolling list that only keeps elements for the rows that
+/// intersect its viewport.
+///
+/// The list is as tall as its rows, up to the maximum of its constraint or
+/// the fallback height if the constraint has none, and scrolls like a
+/// vertical `ScrollElement`. Rows are as wide as the list and as tall as they
+/// lay out, rows that were never visible use the height from the data source. After every layout the list builds the rows that
+/// became visible and removes the ones that left the viewport.
+pub struct ListElement {
+    source: Box<ListDataSource>,
+    rows: Vec<(usize, ElementId)>,
+    heights: Vec<f32>,
+    offsets: Vec<f32>,
+    visible: Range<usize>,
+    reload: bool,
+    fallback_height: f32,
+    scroll: ScrollState,
+}
+
+impl ListElement {
+    pub fn new(source: Box<ListDataSource>) -> ListElement {
+        ListElement {
+            source,
+            rows: vec![],
+            heights: vec![],
+            offsets: vec![0.0],
+            visible: 0..0,
+            reload: true,
+            fallback_height: 300.0,
+            scroll: ScrollState::new(),
+        }
+    }
+
+    /// Largest height of the list in logical units when its constraint does
+    /// not limit it, e.g. inside a vertical `ScrollElement`, as it would build
+    /// every row otherwise.
+    pub fn with_fallback_height(mut self, fallback_height: f32) -> ListElement {
+        self.fallback_height = fallback_height;
+        self
+    }
+
+    pub fn get_source_mut(&mut self) -> &mut ListDataSource {
+        self.source.as_mut()
+    }
+
+    /// Drops all rows and row heights, e.g. after the data source changed.
+    pub fn reload(&mut self) {
+        self.reload = true;
+    }
+
+    /// Rows that currently have elements, with their elements.
+    pub fn get_rows(&self) -> &Vec<(usize, ElementId)> {
+        &self.rows
+    }
+
+    /// Scrolls as little as possible to show all of `row`.
+    pub fn scroll_to_row(&mut self, row: usize) {
+        if row >= self.heights.len() {
+            return;
+        }
+        let (top, bottom) = (self.offsets[row], self.offsets[row + 1]);
+        let (offset, viewport) = (self.get_offset(), self.scroll.get_viewport());
+        if top < offset {
+            self.scroll_to(top);
+        } else if bottom > offset + viewport {
+            self.scroll_to(bottom - viewport);
+        }
+    }
+
+    fn content(&self) -> f32 {
+        *self.offsets.last().unwrap()
+    }
+
+    fn estimate_heights(&mut self) {
+        let source = &self.source;
+        self.heights = (0..source.row_count()).map(|row| source.row_height(row)).collect();
+        self.update_offsets(0);
+    }
+
+    fn update_offsets(&mut self, from: usize) {
+        self.offsets.truncate(from + 1);
+        for row in from..self.heights.len() {
+            let offset = self.offsets[row] + self.heights[row];
+            self.offsets.push(offset);
+        }
+    }
+
+    /// Row that covers `y`, with `y` inside the content.
+    fn row_at(&self, y: f32) -> usize {
+        // first offset below or at `y`
+        let (mut low, mut high) = (0, self.heights.len());
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if self.offsets[middle] <= y {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+
+    fn row_of(&self, id: ElementId) -> Option<usize> {
+        self.rows.iter().find(|&&(_, element)| element == id).map(|&(row, _)| row)
+    }
+}
+
+impl Scrollable for ListElement {
+    fn get_scroll_state(&self) -> &ScrollState {
+        &self.scroll
+    }
+
+    fn get_scroll_state_mut(&mut self) -> &mut ScrollState {
+        &mut self.scroll
+    }
+}
+
+impl LayoutElement for ListElement {
+    fn paint(&mut self, rect: Rect, context: &mut RenderContext, style: &Style) {
+        paint_box(rect, context, style);
+        self.scroll.paint(Axis::Vertical, rect, context);
+    }
+
+    #[allow(unused)]
+    fn paint_after(&mut self, rect: Rect, context: &mut RenderContext, style: &Style) {
+        self.scroll.paint_after(Axis::Vertical, rect, context);
+    }
+
+    #[allow(unused)]
+    fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<ElementId>, requested: Option<Size>) -> Result<LayoutResult, SurfaceError> {
+        if requested.is_none() && (self.reload || self.heights.len() != self.source.row_count()) {
+            self.estimate_heights();
+        }
+
+        let min_width = if constraint.has_bounded_width() { constraint.max_width } else { 0.0 };
+        let ix = context.laid_out_children();
+        if ix < children.len() {
+            return Ok(LayoutResult::LayoutChild(children[ix], Constraint::new(min_width, constraint.max_width, 0.0, UNBOUNDED)));
+        }
+
+        // rows that turned out to have a different height move the rows below
+        let mut width = min_width;
+        let mut changed = None;
+        for child in children.iter() {
+            let size = context.get_child_size(*child)?;
+            width = width.max(size.width);
+            match self.row_of(*child) {
+                Some(row) if !self.reload && row < self.heights.len() && self.heights[row] != size.height => {
+                    self.heights[row] = size.height;
+                    changed = Some(changed.unwrap_or(row).min(row));
+                },
+                _ => (),
+            }
+        }
+        if let Some(row) = changed {
+            self.update_offsets(row);
+        }
+
+        // without a limit every row would be visible
+        let max_height = if constraint.has_bounded_height() { constraint.max_height } else { self.fallback_height };
+        let content = self.content();
+        let viewport = content.min(max_height).max(constraint.min_height);
+        self.scroll.resize(viewport, content);
+        let offset = self.get_offset();
+        self.visible = if self.heights.is_empty() || viewport <= 0.0 {
+            0..0
+        } else {
+            let first = self.row_at(offset);
+            let mut last = self.row_at(offset + viewport);
+            if last > first && self.offsets[last] >= offset + viewport {
+                last -= 1;
+            }
+            first..last + 1
+        };
+
+        for child in children.iter() {
+            let y = match self.row_of(*child) {
+                Some(row) if row < self.heights.len() => self.offsets[row],
+                _ => 0.0,
+            };
+            context.position_child(*child, 0.0, y)?;
+        }
+
+        Ok(LayoutResult::Done(Size::new(width.min(constraint.max_width), viewport)))
+    }
+
+    fn update(&mut self, id: ElementId, surface: &mut Surface) -> Result<(), SurfaceError> {
+        let visible = self.visible.clone();
+        let reload = self.reload;
+        self.reload = false;
+
+        let mut rows = vec![];
+        for (row, element) in self.rows.drain(..) {
+            if !reload && row >= visible.start && row < visible.end && surface.contains(element) {
+                rows.push((row, element));
+            } else if surface.contains(element) {
+                surface.remove(element)?;
+            }
+        }
+
+        for row in visible {
+            if !rows.iter().any(|&(r, _)| r == row) {
+                let element = self.source.build_row(row, surface)?;
+                surface.set_parent(element, id)?;
+                rows.push((row, element));
+            }
+        }
+
+        rows.sort_by_key(|&(row, _)| row);
+        self.rows = rows;
+        Ok(())
+    }
+
+    #[allow(unused)]
+    fn min_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        Ok(0.0)
+    }
+
+    /// Height of all rows, using the estimates for rows that were never laid
+    /// out, up to the fallback height.
+    #[allow(unused)]
+    fn max_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        let content = if self.heights.len() == self.source.row_count() {
+            self.content()
+        } else {
+            let source = &self.source;
+            (0..source.row_count()).map(|row| source.row_height(row)).sum()
+        };
+        Ok(content.min(self.fallback_height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::elements::{FixedElement, ScrollElement};
+
+    /// Rows 20 high.
+    struct Rows;
+
+    impl ListDataSource for Rows {
+        fn row_count(&self) -> usize {
+            1000
+        }
+
+        #[allow(unused)]
+        fn row_height(&self, row: usize) -> f32 {
+            20.0
+        }
+
+        #[allow(unused)]
+        fn build_row(&mut self, row: usize, surface: &mut Surface) -> Result<ElementId, SurfaceError> {
+            Ok(surface.insert(Box::new(FixedElement::new(10.0, 20.0))))
+        }
+    }
+
+    #[test]
+    fn unbounded_lists_use_the_fallback_height() {
+        let mut surface = Surface::new(Size::new(100.0, 100.0));
+        let root = surface.insert(Box::new(ScrollElement::new(Axis::Vertical)));
+        surface.set_root(root).unwrap();
+        let list = surface.insert(Box::new(ListElement::new(Box::new(Rows)).with_fallback_height(60.0)));
+        surface.set_parent(list, root).unwrap();
+
+        surface.layout().unwrap();
+        let rows: Vec<usize> = surface.get_element::<ListElement>(list).unwrap().get_rows().iter().map(|&(row, _)| row).collect();
+        assert_eq!(rows, vec![0, 1, 2]);
+        assert_eq!(surface.get_element::<ListElement>(list).unwrap().max_offset(), 1000.0 * 20.0 - 60.0);
+    }
+}
@@ -1,8 +1,23 @@
 pub mod box_element;
 pub mod fixed_element;
 pub mod flex_element;
+pub mod grid_element;
+pub mod list_element;
+pub mod scroll_element;
+pub mod stack_element;
+pub mod text_element;
 
 pub use self::box_element::BoxElement;
 pub use self::fixed_element::FixedElement;
 pub use self::flex_element::FlexElement;
 pub use self::flex_element::Axis;
+pub use self::grid_element::GridElement;
+pub use self::grid_element::Track;
+pub use self::grid_element::TrackBreadth;
+pub use self::list_element::ListElement;
+pub use self::list_element::ListDataSource;
+pub use self::scroll_element::ScrollElement;
+pub use self::scroll_element::Scrollable;
+pub use self::scroll_element::ScrollState;
+pub use self::stack_element::StackElement;
+pub use self::text_element::TextElement;
@@ -0,0 +1,214 @@
+use ::layout::*;
+use ::constraint::*;
+use ::element_id::*;
+use ::error::*;
+use ::size::*;
+use ::surface::*;
+use ::style::*;
+use ::rect::*;
+use ::color::*;
+use ::rendering::command::*;
+use super::flex_element::Axis;
+
+/// Shows a window into content that is larger than the element along its
+/// axis.
+///
+/// Children are laid out without a limit along the axis and stacked at the
+/// origin. The element is as large as its content, up to the maximum of its
+/// constraint, and hides everything outside of its padding box. The content
+/// is moved by the scroll offset, a scrollbar along the far edge shows the
+/// visible part. Use `Surface::get_element_mut` and `Scrollable` to scroll.
+pub struct ScrollElement {
+    axis: Axis,
+    scroll: ScrollState,
+}
+
+impl ScrollElement {
+    pub fn new(axis: Axis) -> ScrollElement {
+        ScrollElement {
+            axis,
+            scroll: ScrollState::new(),
+        }
+    }
+}
+
+impl Scrollable for ScrollElement {
+    fn get_scroll_state(&self) -> &ScrollState {
+        &self.scroll
+    }
+
+    fn get_scroll_state_mut(&mut self) -> &mut ScrollState {
+        &mut self.scroll
+    }
+}
+
+/// Scroll offset and scrollbar of a `Scrollable` element.
+pub struct ScrollState {
+    offset: f32,
+    viewport: f32,
+    content: f32,
+    scrollbar_width: f32,
+    scrollbar_color: Rgba,
+}
+
+impl ScrollState {
+    pub(crate) fn new() -> ScrollState {
+        ScrollState {
+            offset: 0.0,
+            viewport: 0.0,
+            content: 0.0,
+            scrollbar_width: 6.0,
+            scrollbar_color: Rgba::new(0.5, 0.5, 0.5, 0.8),
+        }
+    }
+
+    pub(crate) fn get_viewport(&self) -> f32 {
+        self.viewport
+    }
+
+    /// Takes the sizes of a new layout and keeps the offset inside the
+    /// content.
+    pub(crate) fn resize(&mut self, viewport: f32, content: f32) {
+        self.viewport = viewport;
+        self.content = content;
+        self.offset = self.offset.min((content - viewport).max(0.0));
+    }
+
+    /// Clips to the padding box and moves the content by the offset, undone
+    /// by `paint_after`.
+    pub(crate) fn paint(&self, axis: Axis, rect: Rect, context: &mut RenderContext) {
+        let (x, y) = axis.pack(self.offset, 0.0);
+        let inner = rect.inset(context.get_border());
+        context.add_command(Command::PushClip(inner));
+        context.add_command(Command::PushTranslation(-x, -y));
+    }
+
+    /// Draws the scrollbar on top of the content.
+    pub(crate) fn paint_after(&self, axis: Axis, rect: Rect, context: &mut RenderContext) {
+        context.add_command(Command::PopTranslation);
+        let inner = rect.inset(context.get_border());
+        if let Some(thumb) = scrollbar(axis, inner, self.offset, self.viewport, self.content, self.scrollbar_width) {
+            context.add_command(Command::DrawRect(thumb, self.scrollbar_color));
+        }
+        context.add_command(Command::PopClip);
+    }
+}
+
+/// Elements that show a window into content that is larger than they are.
+/// Offsets are clamped to the sizes of the last layout.
+pub trait Scrollable {
+    fn get_scroll_state(&self) -> &ScrollState;
+
+    fn get_scroll_state_mut(&mut self) -> &mut ScrollState;
+
+    /// Width of the scrollbar in logical units, 0 hides it.
+    fn with_scrollbar_width(mut self, scrollbar_width: f32) -> Self where Self: Sized {
+        self.get_scroll_state_mut().scrollbar_width = scrollbar_width;
+        self
+    }
+
+    fn with_scrollbar_color(mut self, scrollbar_color: Rgba) -> Self where Self: Sized {
+        self.get_scroll_state_mut().scrollbar_color = scrollbar_color;
+        self
+    }
+
+    fn get_offset(&self) -> f32 {
+        self.get_scroll_state().offset
+    }
+
+    /// Largest offset that still fills the viewport, as of the last layout.
+    fn max_offset(&self) -> f32 {
+        let scroll = self.get_scroll_state();
+        (scroll.content - scroll.viewport).max(0.0)
+    }
+
+    fn scroll_to(&mut self, offset: f32) {
+        let offset = offset.max(0.0).min(self.max_offset());
+        self.get_scroll_state_mut().offset = offset;
+    }
+
+    fn scroll_by(&mut self, delta: f32) {
+        let offset = self.get_offset() + delta;
+        self.scroll_to(offset);
+    }
+}
+
+/// Thumb of a scrollbar along the far edge of `rect`, if the content does not
+/// fit into the viewport.
+fn scrollbar(axis: Axis, rect: Rect, offset: f32, viewport: f32, content: f32, width: f32) -> Option<Rect> {
+    if width <= 0.0 || content <= viewport {
+        return None;
+    }
+
+    let track = axis.major((rect.width, rect.height));
+    let thumb = (track * viewport / content).max(width).min(track);
+    let position = (track - thumb) * offset.min(content - viewport) / (content - viewport);
+
+    let (x, y) = axis.pack(position, (axis.minor((rect.width, rect.height)) - width).max(0.0));
+    let (thumb_width, thumb_height) = axis.pack(thumb, width);
+    Some(Rect::new(rect.x + x, rect.y + y, thumb_width, thumb_height))
+}
+
+impl LayoutElement for ScrollElement {
+    fn paint(&mut self, rect: Rect, context: &mut RenderContext, style: &Style) {
+        paint_box(rect, context, style);
+        self.scroll.paint(self.axis, rect, context);
+    }
+
+    #[allow(unused)]
+    fn paint_after(&mut self, rect: Rect, context: &mut RenderContext, style: &Style) {
+        self.scroll.paint_after(self.axis, rect, context);
+    }
+
+    #[allow(unused)]
+    fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<ElementId>, requested: Option<Size>) -> Result<LayoutResult, SurfaceError> {
+        let max = (constraint.max_width, constraint.max_height);
+        let min = (constraint.min_width, constraint.min_height);
+
+        let ix = context.laid_out_children();
+        if ix < children.len() {
+            let child_constraint = self.axis.constraint(0.0, UNBOUNDED, 0.0, self.axis.minor(max));
+            return Ok(LayoutResult::LayoutChild(children[ix], child_constraint));
+        }
+
+        let mut content: (f32, f32) = (0.0, 0.0);
+        for child in children.iter() {
+            let size = context.get_child_size(*child)?;
+            context.position_child(*child, 0.0, 0.0)?;
+            content = (content.0.max(size.width), content.1.max(size.height));
+        }
+
+        let major = self.axis.major(content);
+        let viewport = major.max(self.axis.major(min)).min(self.axis.major(max));
+        self.scroll.resize(viewport, major);
+
+        let minor = self.axis.minor(content).max(self.axis.minor(min)).min(self.axis.minor(max));
+        let (width, height) = self.axis.pack(viewport, minor);
+        Ok(LayoutResult::Done(Size::new(width, height)))
+    }
+
+    /// Along its axis the element can shrink to nothing.
+    #[allow(unused)]
+    fn min_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        if self.axis == Axis::Horizontal {
+            return Ok(0.0);
+        }
+        let mut width: f32 = 0.0;
+        for child in children.iter() {
+            width = width.max(context.get_child_min_intrinsic_width(*child, UNBOUNDED)?);
+        }
+        Ok(width)
+    }
+
+    #[allow(unused)]
+    fn min_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        if self.axis == Axis::Vertical {
+            return Ok(0.0);
+        }
+        let mut height: f32 = 0.0;
+        for child in children.iter() {
+            height = height.max(context.get_child_min_intrinsic_height(*child, UNBOUNDED)?);
+        }
+        Ok(height)
+    }
+}
@@ -0,0 +1,83 @@
+use ::layout::*;
+use ::constraint::*;
+use ::element_id::*;
+use ::error::*;
+use ::size::*;
+use ::surface::*;
+use ::style::*;
+
+/// Layers its children on top of each other, later children above earlier
+/// ones.
+///
+/// Children are placed by their `justify_self` horizontally and their
+/// `align_self` vertically, falling back to the alignment of the stack.
+/// `Align::Stretch` makes a child fill the stack along that axis. In
+/// `Direction::Rtl` horizontal alignment starts at the right edge. The stack
+/// is as large as its largest child.
+pub struct StackElement {
+    justify_items: Align,
+    align_items: Align,
+}
+
+impl StackElement {
+    pub fn new() -> StackElement {
+        StackElement {
+            justify_items: Align::Start,
+            align_items: Align::Start,
+        }
+    }
+
+    pub fn with_justify_items(mut self, justify_items: Align) -> StackElement {
+        self.justify_items = justify_items;
+        self
+    }
+
+    pub fn with_align_items(mut self, align_items: Align) -> StackElement {
+        self.align_items = align_items;
+        self
+    }
+
+    fn alignment(&self, context: &LayoutContext, child: ElementId) -> Result<(Align, Align), SurfaceError> {
+        let style = context.get_child_style(child)?;
+        Ok((style.justify_self.unwrap_or(self.justify_items), style.align_self.unwrap_or(self.align_items)))
+    }
+}
+
+fn offset(align: Align, available: f32, size: f32) -> f32 {
+    match align {
+        Align::Start | Align::Stretch | Align::Baseline => 0.0,
+        Align::End => (available - size).max(0.0),
+        Align::Center => (available - size).max(0.0) / 2.0,
+    }
+}
+
+impl LayoutElement for StackElement {
+    #[allow(unused)]
+    fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<ElementId>, requested: Option<Size>) -> Result<LayoutResult, SurfaceError> {
+        let ix = context.laid_out_children();
+        if ix < children.len() {
+            let (justify, align) = self.alignment(context, children[ix])?;
+            let min_width = if justify == Align::Stretch && constraint.has_bounded_width() { constraint.max_width } else { 0.0 };
+            let min_height = if align == Align::Stretch && constraint.has_bounded_height() { constraint.max_height } else { 0.0 };
+            return Ok(LayoutResult::LayoutChild(children[ix], Constraint::new(min_width, constraint.max_width, min_height, constraint.max_height)));
+        }
+
+        let mut size = Size::new(constraint.min_width, constraint.min_height);
+        for child in children.iter() {
+            let child_size = context.get_child_size(*child)?;
+            size.width = size.width.max(child_size.width);
+            size.height = size.height.max(child_size.height);
+        }
+        let size = constraint.constrain(size);
+
+        for child in children.iter() {
+            let child_size = context.get_child_size(*child)?;
+            let (justify, align) = self.alignment(context, *child)?;
+            let x = offset(justify, size.width, child_size.width);
+            let x = context.get_direction().place(x, child_size.width, size.width);
+            let y = offset(align, size.height, child_size.height);
+            context.position_child(*child, x, y)?;
+        }
+        Ok(LayoutResult::Done(size))
+    }
+}
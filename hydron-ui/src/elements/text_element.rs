@@ -0,0 +1,403 @@
+use ::layout::*;
+use ::constraint::*;
+use ::element_id::*;
+use ::error::*;
+use ::size::*;
+use ::surface::*;
+use ::style::*;
+use ::rect::*;
+use ::color::*;
+use ::rendering::command::*;
+use ::text::*;
+
+/// Span with everything taken from the element filled in.
+#[derive(Copy, Clone)]
+struct SpanStyle {
+    font: FontId,
+    size: f32,
+    color: Rgba,
+    decoration: TextDecoration,
+    metrics: FontMetrics,
+    /// Bytes of the text of the element the span covers.
+    start: usize,
+    end: usize,
+}
+
+/// Line of laid out text.
+struct TextLine {
+    /// Glyphs with positions relative to the start of the line.
+    glyphs: Vec<ShapedGlyph>,
+    width: f32,
+    /// Room the line takes above and below its baseline.
+    above: f32,
+    below: f32,
+    /// Start of the baseline relative to the content box.
+    x: f32,
+    y: f32,
+}
+
+/// Paragraph of text made of spans that each have their own style.
+///
+/// The spans are shaped one after the other and the text is wrapped to the
+/// maximum width of the constraint as a whole, see `break_lines`. Every
+/// span takes up its line height around its baseline with the extra space
+/// above the font split evenly between top and bottom, lines are as tall as
+/// their tallest span. Lines past `max_lines` are cut off and the last line
+/// that is kept ends in an ellipsis. Right to left parts of the text are
+/// found with the Unicode bidirectional algorithm, with the direction of the
+/// element as the direction of its paragraphs, and every line is put into
+/// the order it is shown in after it was broken. The element has no
+/// children.
+pub struct TextElement {
+    spans: Vec<Span>,
+    text: String,
+    font: Option<FontId>,
+    color: Rgba,
+    align: TextAlign,
+    line_height: Option<f32>,
+    max_lines: Option<usize>,
+    styles: Vec<SpanStyle>,
+    lines: Vec<TextLine>,
+    baseline: Option<f32>,
+}
+
+impl TextElement {
+    pub fn new<S: Into<String>>(text: S) -> TextElement {
+        TextElement::rich(vec![Span::new(text)])
+    }
+
+    pub fn rich(spans: Vec<Span>) -> TextElement {
+        let mut element = TextElement {
+            spans: vec![],
+            text: String::new(),
+            font: None,
+            color: Rgba::new(0.0, 0.0, 0.0, 1.0),
+            align: TextAlign::Start,
+            line_height: None,
+            max_lines: None,
+            styles: vec![],
+            lines: vec![],
+            baseline: None,
+        };
+        element.set_spans(spans);
+        element
+    }
+
+    /// Font of spans without one, defaults to the default font of the
+    /// `Surface`.
+    pub fn with_font(mut self, font: FontId) -> TextElement {
+        self.font = Some(font);
+        self
+    }
+
+    /// Color of spans without one.
+    pub fn with_color(mut self, color: Rgba) -> TextElement {
+        self.color = color;
+        self
+    }
+
+    pub fn with_align(mut self, align: TextAlign) -> TextElement {
+        self.align = align;
+        self
+    }
+
+    /// Line height of every span as a multiple of its font size, defaults
+    /// to the line height of its font.
+    pub fn with_line_height(mut self, line_height: f32) -> TextElement {
+        self.line_height = Some(line_height);
+        self
+    }
+
+    pub fn with_max_lines(mut self, max_lines: usize) -> TextElement {
+        self.max_lines = Some(max_lines);
+        self
+    }
+
+    /// Text of all spans.
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the spans with a single span of `text`, call
+    /// `Surface::invalidate` to lay it out.
+    pub fn set_text<S: Into<String>>(&mut self, text: S) {
+        self.set_spans(vec![Span::new(text)]);
+    }
+
+    pub fn get_spans(&self) -> &Vec<Span> {
+        &self.spans
+    }
+
+    /// Replaces the spans, call `Surface::invalidate` to lay them out.
+    pub fn set_spans(&mut self, spans: Vec<Span>) {
+        self.text = spans.iter().map(|span| span.text.as_str()).collect();
+        self.spans = spans;
+    }
+
+    /// Span under the point `x`, `y` relative to the content box, as of the
+    /// last layout. Used to find the link that was clicked.
+    pub fn span_at(&self, x: f32, y: f32) -> Option<&Span> {
+        let line = self.lines.iter().find(|line| y >= line.y - line.above && y < line.y + line.below)?;
+        let glyph = line.glyphs.iter().find(|glyph| x >= line.x + glyph.x && x < line.x + glyph.x + glyph.advance)?;
+        self.spans.get(span_of(&self.styles, glyph.index))
+    }
+
+    /// Styles of the spans, none if there is no font to shape them with.
+    fn resolve_styles(&self, context: &LayoutContext) -> Option<Vec<SpanStyle>> {
+        let fonts = context.get_fonts();
+        let default_font = self.font.or(fonts.get_default())?;
+        let units = context.get_units();
+
+        let mut start = 0;
+        Some(self.spans.iter().map(|span| {
+            let size = span.font_size
+                .and_then(|font_size| font_size.resolve(units.font_size, &units))
+                .unwrap_or(units.font_size);
+            let font = span.font.unwrap_or(default_font);
+            let (weight, font_style) = fonts.get_face(font).unwrap_or((FontWeight::NORMAL, FontStyle::Normal));
+            let font = fonts.select(font, span.weight.unwrap_or(weight), span.style.unwrap_or(font_style));
+            let metrics = fonts.metrics(font, size).unwrap_or(FontMetrics {
+                ascent: 0.0,
+                descent: 0.0,
+                line_gap: 0.0,
+            });
+            let end = start + span.text.len();
+            let style = SpanStyle {
+                font,
+                size,
+                color: span.color.unwrap_or(self.color),
+                decoration: span.decoration,
+                metrics,
+                start,
+                end,
+            };
+            start = end;
+            style
+        }).collect())
+    }
+
+    fn bidi(&self, context: &LayoutContext) -> Bidi {
+        Bidi::new(&self.text, context.get_direction() == Direction::Rtl)
+    }
+
+    /// Glyphs of all spans in the order of the text, placed after each
+    /// other. Every part of a span with a single direction is shaped on its
+    /// own.
+    fn shape(&self, context: &LayoutContext, bidi: &Bidi, styles: &Vec<SpanStyle>) -> Vec<ShapedGlyph> {
+        let mut glyphs = vec![];
+        let mut x = 0.0;
+        for style in styles.iter() {
+            for (range, rtl) in bidi.runs(style.start..style.end) {
+                let shaped = context.get_fonts().shape(style.font, style.size, &self.text, range, rtl);
+                let width: f32 = shaped.iter().map(|glyph| glyph.advance).sum();
+                glyphs.extend(shaped.into_iter().map(|glyph| ShapedGlyph {
+                    x: glyph.x + x,
+                    ..glyph
+                }));
+                x += width;
+            }
+        }
+        glyphs
+    }
+
+    /// Room a span takes above and below the baseline of its line.
+    fn extent(&self, style: &SpanStyle) -> (f32, f32) {
+        let metrics = style.metrics;
+        let line_height = self.line_height.map(|line_height| line_height * style.size).unwrap_or(metrics.line_height());
+        let half_leading = (line_height - metrics.ascent - metrics.descent) / 2.0;
+        (half_leading + metrics.ascent, half_leading + metrics.descent)
+    }
+
+    /// Lines when the text is at most `max_width` wide, with their glyphs in
+    /// the order they are shown, not placed yet.
+    fn break_text(&self, context: &LayoutContext, styles: &Vec<SpanStyle>, max_width: f32) -> Vec<TextLine> {
+        let bidi = self.bidi(context);
+        let glyphs = self.shape(context, &bidi, styles);
+        let mut lines: Vec<TextLine> = break_lines(&self.text, &glyphs, max_width).into_iter()
+            .map(|range| {
+                // an empty line gets the height of the span it is in
+                let index = glyphs.get(range.start).map_or(self.text.len(), |glyph| glyph.index);
+                let mut spans = vec![span_of(styles, index)];
+                spans.extend(glyphs[range.clone()].iter().map(|glyph| span_of(styles, glyph.index)));
+
+                let (above, below) = spans.iter()
+                    .filter_map(|span| styles.get(*span))
+                    .map(|style| self.extent(style))
+                    .fold((0.0, 0.0), |(above, below): (f32, f32), (a, b)| (above.max(a), below.max(b)));
+                let mut line_glyphs = glyphs[range].to_vec();
+                trim_end(&self.text, &mut line_glyphs);
+                place(&mut line_glyphs);
+                TextLine {
+                    width: line_width(&self.text, &line_glyphs),
+                    glyphs: line_glyphs,
+                    above,
+                    below,
+                    x: 0.0,
+                    y: 0.0,
+                }
+            })
+            .collect();
+
+        if let Some(max_lines) = self.max_lines {
+            if lines.len() > max_lines {
+                lines.truncate(max_lines);
+                if let Some(last) = lines.last_mut() {
+                    self.ellipsize(context.get_fonts(), styles, last, max_width);
+                }
+            }
+        }
+        for line in lines.iter_mut() {
+            line.glyphs = bidi.reorder(&line.glyphs);
+        }
+        lines
+    }
+
+    /// Drops glyphs from the end of `line`, in the order of the text, until an
+    /// ellipsis in the style of the last glyph fits after it, and appends the
+    /// ellipsis.
+    fn ellipsize(&self, fonts: &Fonts, styles: &Vec<SpanStyle>, line: &mut TextLine, max_width: f32) {
+        let index = match line.glyphs.last() {
+            Some(glyph) => glyph.index,
+            None => return,
+        };
+        let style = match styles.get(span_of(styles, index)) {
+            Some(style) => *style,
+            None => return,
+        };
+        let shape = |text: &str| fonts.shape(style.font, style.size, text, 0..text.len(), false);
+        let mut ellipsis = shape("\u{2026}");
+        if ellipsis.iter().any(|glyph| glyph.id == 0) {
+            ellipsis = shape("...");
+        }
+        let ellipsis_width: f32 = ellipsis.iter().map(|glyph| glyph.advance).sum();
+
+        truncate(&self.text, &mut line.glyphs, max_width - ellipsis_width);
+        let width = line_width(&self.text, &line.glyphs);
+        line.glyphs.extend(ellipsis.into_iter().map(|glyph| ShapedGlyph {
+            x: glyph.x + width,
+            index,
+            ..glyph
+        }));
+        line.width = width + ellipsis_width;
+    }
+
+    fn height(&self, context: &LayoutContext, width: f32) -> f32 {
+        match self.resolve_styles(context) {
+            Some(styles) => self.break_text(context, &styles, width).iter()
+                .map(|line| line.above + line.below)
+                .sum(),
+            None => 0.0,
+        }
+    }
+
+    fn paint_run(&self, context: &mut RenderContext, origin: (f32, f32), style: &SpanStyle, glyphs: &[ShapedGlyph]) {
+        let (first, last) = match (glyphs.first(), glyphs.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return,
+        };
+        context.add_command(Command::DrawText(GlyphRun {
+            font: style.font,
+            size: style.size,
+            color: style.color,
+            origin,
+            glyphs: glyphs.to_vec(),
+        }));
+
+        let thickness = (style.size / 16.0).max(1.0);
+        let y = match style.decoration {
+            TextDecoration::None => return,
+            TextDecoration::Underline => origin.1 + style.size / 10.0,
+            TextDecoration::LineThrough => origin.1 - style.metrics.ascent * 0.3 - thickness / 2.0,
+        };
+        let x = origin.0 + first.x;
+        let width = last.x + last.advance - first.x;
+        context.add_command(Command::DrawRect(Rect::new(x, y, width, thickness), style.color));
+    }
+}
+
+/// Index of the span the byte `index` of the text belongs to.
+fn span_of(styles: &Vec<SpanStyle>, index: usize) -> usize {
+    styles.iter().rposition(|style| style.start <= index).unwrap_or(0)
+}
+
+impl LayoutElement for TextElement {
+    fn paint(&mut self, rect: Rect, context: &mut RenderContext, style: &Style) {
+        paint_box(rect, context, style);
+
+        let content = rect.inset(context.get_border() + context.get_padding());
+        for line in self.lines.iter() {
+            let origin = (content.x + line.x, content.y + line.y);
+            let mut start = 0;
+            while start < line.glyphs.len() {
+                let span = span_of(&self.styles, line.glyphs[start].index);
+                let end = line.glyphs[start..].iter()
+                    .position(|glyph| span_of(&self.styles, glyph.index) != span)
+                    .map_or(line.glyphs.len(), |length| start + length);
+                self.paint_run(context, origin, &self.styles[span], &line.glyphs[start..end]);
+                start = end;
+            }
+        }
+    }
+
+    #[allow(unused)]
+    fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<ElementId>, requested: Option<Size>) -> Result<LayoutResult, SurfaceError> {
+        self.styles = self.resolve_styles(context).unwrap_or(vec![]);
+        self.lines = self.break_text(context, &self.styles, constraint.max_width);
+
+        let width = self.lines.iter().fold(0.0, |width: f32, line| width.max(line.width));
+        let height = self.lines.iter().map(|line| line.above + line.below).sum();
+        let size = constraint.constrain(Size::new(width, height));
+
+        let direction = context.get_direction();
+        let align = self.align;
+        let mut y = 0.0;
+        for line in self.lines.iter_mut() {
+            let free = (size.width - line.width).max(0.0);
+            line.x = match align {
+                TextAlign::Start => direction.place(0.0, line.width, size.width),
+                TextAlign::End => direction.place(free, line.width, size.width),
+                TextAlign::Left => 0.0,
+                TextAlign::Right => free,
+                TextAlign::Center => free / 2.0,
+            };
+            line.y = y + line.above;
+            y += line.above + line.below;
+        }
+        self.baseline = self.lines.first().map(|line| line.y);
+        Ok(LayoutResult::Done(size))
+    }
+
+    /// Width of the longest word.
+    #[allow(unused)]
+    fn min_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        Ok(match self.resolve_styles(context) {
+            Some(styles) => min_content_width(&self.text, &self.shape(context, &self.bidi(context), &styles)),
+            None => 0.0,
+        })
+    }
+
+    /// Width of the longest line when only hard breaks end lines.
+    #[allow(unused)]
+    fn max_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        Ok(match self.resolve_styles(context) {
+            Some(styles) => self.break_text(context, &styles, UNBOUNDED).iter()
+                .map(|line| line.width)
+                .fold(0.0, f32::max),
+            None => 0.0,
+        })
+    }
+
+    #[allow(unused)]
+    fn min_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        Ok(self.height(context, width))
+    }
+
+    #[allow(unused)]
+    fn max_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        Ok(self.height(context, width))
+    }
+
+    fn baseline(&self) -> Option<f32> {
+        self.baseline
+    }
+}
@@ -0,0 +1,29 @@
+use ::std::error::Error;
+use ::std::fmt;
+use super::element_id::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SurfaceError {
+    DeadElement(ElementId),
+    Detached(ElementId),
+    Cycle(ElementId, ElementId),
+    OutOfBounds(ElementId, usize),
+    WrongType(ElementId),
+    Busy(ElementId),
+}
+
+impl fmt::Display for SurfaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SurfaceError::DeadElement(id) => write!(f, "element {} (generation {}) does not exist", id.index(), id.generation()),
+            SurfaceError::Detached(id) => write!(f, "element {} has no parent", id.index()),
+            SurfaceError::Cycle(child, parent) => write!(f, "element {} can not become a child of its descendant {}", child.index(), parent.index()),
+            SurfaceError::OutOfBounds(parent, position) => write!(f, "position {} is out of bounds for the children of element {}", position, parent.index()),
+            SurfaceError::WrongType(id) => write!(f, "element {} is not of the requested type", id.index()),
+            SurfaceError::Busy(id) => write!(f, "element {} is being updated", id.index()),
+        }
+    }
+}
+
+impl Error for SurfaceError {
+}
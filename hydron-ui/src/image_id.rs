@@ -0,0 +1,18 @@
+/// Handle to an image, handed out by the image registry of the application
+/// that draws the commands of a `Surface`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ImageId {
+    index: usize,
+}
+
+impl ImageId {
+    pub fn new(index: usize) -> ImageId {
+        ImageId {
+            index,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
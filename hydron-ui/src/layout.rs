@@ -1,41 +1,130 @@
-use super::rendering::command::*;
 use super::constraint::*;
+use super::element_id::*;
+use super::error::*;
 use super::surface::*;
 use super::size::*;
 use super::rect::*;
 use super::style::*;
+use ::std::any::Any;
 
 #[derive(Debug, Copy, Clone)]
 pub enum LayoutResult {
     Done(Size),
-    LayoutChild(usize, Constraint),
+    LayoutChild(ElementId, Constraint),
 }
 
-pub trait LayoutElement {
+/// Paints the shadows, background and border of an element with the border
+/// box `rect`.
+pub fn paint_box(rect: Rect, context: &mut RenderContext, style: &Style) {
+    context.draw_shadows(rect, &style.box_shadow, false);
+    context.draw_background(rect, &style.background);
+    context.draw_shadows(rect, &style.box_shadow, true);
+    context.draw_border(rect, &style.border);
+}
 
-    #[allow(unused)]
+/// Gives access to the concrete type behind a `LayoutElement`, see
+/// `Surface::get_element_mut`.
+pub trait AsAny {
+    fn as_any(&self) -> &Any;
+    fn as_any_mut(&mut self) -> &mut Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut Any {
+        self
+    }
+}
+
+pub trait LayoutElement: AsAny {
+
+    /// Paints the element into its border box `rect`. Defaults to
+    /// `paint_box`.
     fn paint(&mut self, rect: Rect, context: &mut RenderContext, style: &Style) {
-        match style.background {
-            Background::Color(color) => context.add_command(Command::DrawRect(rect, color)),
-            _ => (),
-        }
+        paint_box(rect, context, style);
+    }
+
+    /// Called after the children of the element were painted, e.g. to
+    /// close a clip opened in `paint` or to draw on top of the children.
+    #[allow(unused)]
+    fn paint_after(&mut self, rect: Rect, context: &mut RenderContext, style: &Style) {
     }
 
+    /// Lays out every child with the maximum of `constraint` and stacks
+    /// them at the origin. The element is as large as its largest child.
     #[allow(unused)]
-    fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<usize>, requested: Option<Size>) -> LayoutResult {
-        match children.len() {
-            0 => LayoutResult::Done(Size::new(constraint.min_width, constraint.min_height)),
-            1 => {
-                if let Some(size) = requested {
-                    context.position_child(children[0], 0, 0);
-                    LayoutResult::Done(size)
-                } else {
-                    LayoutResult::LayoutChild(children[0], Constraint::new(0, constraint.max_width, 0, constraint.max_height))
-                }
-            }
-            _ => {
-                unimplemented!()
-            }
+    fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<ElementId>, requested: Option<Size>) -> Result<LayoutResult, SurfaceError> {
+        let ix = context.laid_out_children();
+        if ix < children.len() {
+            return Ok(LayoutResult::LayoutChild(children[ix], Constraint::loose(constraint.max_width, constraint.max_height)));
+        }
+
+        let mut size = Size::new(constraint.min_width, constraint.min_height);
+        for child in children.iter() {
+            let child_size = context.get_child_size(*child)?;
+            context.position_child(*child, 0.0, 0.0)?;
+            size.width = size.width.max(child_size.width);
+            size.height = size.height.max(child_size.height);
+        }
+        Ok(LayoutResult::Done(constraint.constrain(size)))
+    }
+
+    /// Smallest width the element can take without its content overflowing,
+    /// given a content box `height` that may be `UNBOUNDED`. Sizes exclude
+    /// the margin, border and padding of the element. Defaults to the largest child.
+    fn min_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        let mut width: f32 = 0.0;
+        for child in children.iter() {
+            width = width.max(context.get_child_min_intrinsic_width(*child, height)?);
+        }
+        Ok(width)
+    }
+
+    /// Width the element takes when it is not limited, e.g. the width of
+    /// text without any line breaks.
+    fn max_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        let mut width: f32 = 0.0;
+        for child in children.iter() {
+            width = width.max(context.get_child_max_intrinsic_width(*child, height)?);
+        }
+        Ok(width)
+    }
+
+    fn min_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        let mut height: f32 = 0.0;
+        for child in children.iter() {
+            height = height.max(context.get_child_min_intrinsic_height(*child, width)?);
+        }
+        Ok(height)
+    }
+
+    fn max_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+        let mut height: f32 = 0.0;
+        for child in children.iter() {
+            height = height.max(context.get_child_max_intrinsic_height(*child, width)?);
         }
-    }    
+        Ok(height)
+    }
+
+    /// Distance from the top of the content box to the first baseline of
+    /// the element, queried after `layout` finished.
+    fn baseline(&self) -> Option<f32> {
+        None
+    }
+
+    /// Called after every layout pass with access to the `Surface`, e.g. to
+    /// add or remove children. If this leaves elements dirty, the tree is
+    /// laid out again.
+    #[allow(unused)]
+    fn update(&mut self, id: ElementId, surface: &mut Surface) -> Result<(), SurfaceError> {
+        Ok(())
+    }
+
+    /// Called when the element is removed from its `Surface`, right before
+    /// it is dropped.
+    fn teardown(&mut self) {
+    }
 }
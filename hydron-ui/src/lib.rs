@@ -1,12 +1,18 @@
-#![feature(vec_remove_item)]
+extern crate rusttype;
+extern crate unicode_bidi;
+extern crate xi_unicode;
 
 pub mod elements;
 pub mod style;
 pub mod rendering;
+pub mod text;
 
 mod color;
 mod component;
 mod constraint;
+mod element_id;
+mod image_id;
+mod error;
 mod layout;
 mod rect;
 mod size;
@@ -14,6 +20,9 @@ mod surface;
 
 pub use color::*;
 pub use constraint::*;
+pub use element_id::*;
+pub use image_id::*;
+pub use error::*;
 pub use layout::*;
 pub use rect::*;
 pub use size::*;
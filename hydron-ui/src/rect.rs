@@ -1,13 +1,15 @@
-#[derive(Debug, Copy, Clone)]
+use ::style::TRBL;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Rect {
-    pub x: usize,
-    pub y: usize,
-    pub width: usize,
-    pub height: usize,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
 }
 
 impl Rect {
-    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Rect {
         Rect {
             x,
             y,
@@ -15,4 +17,24 @@ impl Rect {
             height,
         }
     }
+
+    /// Moves the edges inwards by `insets`, without going below zero size.
+    pub fn inset(&self, insets: TRBL<f32>) -> Rect {
+        Rect::new(
+            self.x + insets.left,
+            self.y + insets.top,
+            (self.width - insets.horizontal()).max(0.0),
+            (self.height - insets.vertical()).max(0.0),
+        )
+    }
+
+    /// Scales the rect to device pixels and rounds its edges to whole
+    /// pixels, so rects sharing an edge still share it after rounding.
+    pub fn snap(&self, scale_factor: f32) -> Rect {
+        let left = (self.x * scale_factor).round();
+        let top = (self.y * scale_factor).round();
+        let right = ((self.x + self.width) * scale_factor).round();
+        let bottom = ((self.y + self.height) * scale_factor).round();
+        Rect::new(left, top, right - left, bottom - top)
+    }
 }
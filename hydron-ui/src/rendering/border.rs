@@ -0,0 +1,59 @@
+use ::rect::*;
+use ::color::*;
+use ::style::*;
+
+/// Rects that make up the border of `rect`, with `widths` being the resolved
+/// widths of `border`. Top and bottom span the whole width, left and right
+/// fit between them.
+pub fn border_rects(rect: Rect, widths: TRBL<f32>, border: &TRBL<Border>) -> Vec<(Rect, Rgba)> {
+    let mut rects = vec![];
+    let inner_y = rect.y + widths.top;
+    let inner_height = (rect.height - widths.vertical()).max(0.0);
+
+    let mut edge = |x: f32, y: f32, length: f32, thickness: f32, horizontal: bool, border: Border| {
+        if thickness <= 0.0 || length <= 0.0 {
+            return;
+        }
+        for (start, end) in segments(length, thickness, border.style) {
+            let rect = if horizontal {
+                Rect::new(x + start, y, end - start, thickness)
+            } else {
+                Rect::new(x, y + start, thickness, end - start)
+            };
+            rects.push((rect, border.color));
+        }
+    };
+
+    edge(rect.x, rect.y, rect.width, widths.top, true, border.top);
+    edge(rect.x + rect.width - widths.right, inner_y, inner_height, widths.right, false, border.right);
+    edge(rect.x, rect.y + rect.height - widths.bottom, rect.width, widths.bottom, true, border.bottom);
+    edge(rect.x, inner_y, inner_height, widths.left, false, border.left);
+    rects
+}
+
+/// Length of the dashes and the gaps between them of a border `thickness`
+/// wide, none if the border is solid or not drawn.
+pub fn dash_pattern(style: BorderStyle, thickness: f32) -> Option<(f32, f32)> {
+    match style {
+        BorderStyle::None | BorderStyle::Solid => None,
+        BorderStyle::Dashed => Some((thickness * 3.0, thickness * 2.0)),
+        BorderStyle::Dotted => Some((thickness, thickness)),
+    }
+}
+
+/// Drawn parts of an edge `length` long, as start and end along the edge.
+fn segments(length: f32, thickness: f32, style: BorderStyle) -> Vec<(f32, f32)> {
+    let (dash, gap) = match (style, dash_pattern(style, thickness)) {
+        (BorderStyle::None, _) => return vec![],
+        (_, None) => return vec![(0.0, length)],
+        (_, Some(pattern)) => pattern,
+    };
+
+    let mut segments = vec![];
+    let mut start = 0.0;
+    while start < length {
+        segments.push((start, (start + dash).min(length)));
+        start += dash + gap;
+    }
+    segments
+}
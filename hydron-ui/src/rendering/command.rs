@@ -1,6 +1,121 @@
 use ::rect::*;
 use ::color::*;
+use ::image_id::*;
+use ::style::{Corners, ImageFit, TRBL};
+use ::text::*;
 
+/// Colors that change over a rect, with stops given as position from 0 to
+/// 1 and color.
+#[derive(Debug, Clone)]
+pub enum Gradient {
+    /// Goes from the first to the last color between `start` and `end`.
+    Linear {
+        start: (f32, f32),
+        end: (f32, f32),
+        stops: Vec<(f32, Rgba)>,
+    },
+    /// Goes from the first color at `center` to the last color `radius`
+    /// away from it.
+    Radial {
+        center: (f32, f32),
+        radius: f32,
+        stops: Vec<(f32, Rgba)>,
+    },
+}
+
+impl Gradient {
+    pub fn get_stops(&self) -> &Vec<(f32, Rgba)> {
+        match *self {
+            Gradient::Linear { ref stops, .. } => stops,
+            Gradient::Radial { ref stops, .. } => stops,
+        }
+    }
+}
+
+/// Glyphs of one font, size and color placed along a baseline.
+#[derive(Debug, Clone)]
+pub struct GlyphRun {
+    pub font: FontId,
+    pub size: f32,
+    pub color: Rgba,
+    /// Start of the baseline, glyphs are placed relative to it.
+    pub origin: (f32, f32),
+    pub glyphs: Vec<ShapedGlyph>,
+}
+
+/// Side of a rect, in the order of `TRBL`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Side {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+/// Drawing commands produced by `Surface::paint`. Positions and sizes are in
+/// device pixels.
 pub enum Command {
     DrawRect(Rect, Rgba),
+    /// Rect with a radius for each corner.
+    DrawRoundedRect(Rect, Corners<f32>, Rgba),
+    /// Rounded rect filled with a gradient. Colors before the first and
+    /// after the last stop are those of the first and last stop.
+    DrawGradient(Rect, Corners<f32>, Gradient),
+    /// Image drawn into `rect` as `fit` describes, see `image_rects`, and
+    /// cut off outside of the rounded rect. `scale` is the size of one pixel
+    /// of the image.
+    DrawImage {
+        rect: Rect,
+        radii: Corners<f32>,
+        image: ImageId,
+        fit: ImageFit,
+        scale: f32,
+    },
+    /// Text, with each glyph drawn at the closest whole pixel.
+    DrawText(GlyphRun),
+    /// Ring between the edges of a rounded rect and the same rect inset by
+    /// `widths`, with radii that shrink by the adjacent widths.
+    DrawRoundedBorder {
+        rect: Rect,
+        radii: Corners<f32>,
+        widths: TRBL<f32>,
+        /// Part of the ring that is drawn, all of it if none. Corners are
+        /// split between their sides along the line from the outer to the
+        /// inner corner.
+        side: Option<Side>,
+        color: Rgba,
+        /// Length of the dashes and the gaps between them along the side,
+        /// solid if none.
+        dash: Option<(f32, f32)>,
+    },
+    /// Rounded rect `shape` blurred by a gaussian with a standard deviation
+    /// of half of `blur`.
+    DrawShadow {
+        shape: Rect,
+        radii: Corners<f32>,
+        blur: f32,
+        color: Rgba,
+    },
+    /// Fills the rounded rect `rect` with a shadow, except for the blurred
+    /// rounded rect `shape`.
+    DrawInsetShadow {
+        rect: Rect,
+        radii: Corners<f32>,
+        shape: Rect,
+        shape_radii: Corners<f32>,
+        blur: f32,
+        color: Rgba,
+    },
+    /// Hides everything drawn outside of the rect until the matching
+    /// `PopClip`. Nested clips intersect.
+    PushClip(Rect),
+    PopClip,
+    /// Moves everything drawn until the matching `PopTranslation`, including
+    /// clip rects, by the given amount.
+    PushTranslation(f32, f32),
+    PopTranslation,
+    /// Draws everything until the matching `PopOpacity` into a layer of
+    /// its own, which is then blended as a whole with the given opacity.
+    PushOpacity(f32),
+    PopOpacity,
 }
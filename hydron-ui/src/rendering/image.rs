@@ -0,0 +1,112 @@
+use ::rect::*;
+use ::size::*;
+use ::style::*;
+
+/// Parts of an image of `image` pixels drawn into `rect` as `fit`
+/// describes, as destination rect and source rect in pixels of the image.
+/// `scale` is the size of one pixel of the image in the units of `rect`.
+/// Source rects of `ImageFit::Repeat` extend past the image, which repeats
+/// in both directions.
+pub fn image_rects(rect: Rect, image: Size, fit: ImageFit, scale: f32) -> Vec<(Rect, Rect)> {
+    if image.width <= 0.0 || image.height <= 0.0 || rect.width <= 0.0 || rect.height <= 0.0 {
+        return vec![];
+    }
+
+    let source = Rect::new(0.0, 0.0, image.width, image.height);
+    let factor_x = rect.width / image.width;
+    let factor_y = rect.height / image.height;
+    match fit {
+        ImageFit::Fill => vec![(rect, source)],
+        ImageFit::Contain => {
+            let factor = factor_x.min(factor_y);
+            let (width, height) = (image.width * factor, image.height * factor);
+            let destination = Rect::new(rect.x + (rect.width - width) / 2.0, rect.y + (rect.height - height) / 2.0, width, height);
+            vec![(destination, source)]
+        },
+        ImageFit::Cover => {
+            let factor = factor_x.max(factor_y);
+            let (width, height) = (rect.width / factor, rect.height / factor);
+            let source = Rect::new((image.width - width) / 2.0, (image.height - height) / 2.0, width, height);
+            vec![(rect, source)]
+        },
+        ImageFit::Repeat => vec![(rect, Rect::new(0.0, 0.0, rect.width / scale, rect.height / scale))],
+        ImageFit::NineSlice(slices) => nine_slice(rect, image, slices, scale),
+    }
+}
+
+/// Corners keep their size unless they do not fit into `rect`, then all of
+/// them shrink by the same factor.
+fn nine_slice(rect: Rect, image: Size, slices: TRBL<f32>, scale: f32) -> Vec<(Rect, Rect)> {
+    let fit = |a: f32, b: f32, length: f32| if a + b > length { length / (a + b) } else { 1.0 };
+    let slices = slices.map(|slice| slice.max(0.0));
+    let slices = slices.map(|slice| slice * fit(slices.left, slices.right, image.width).min(fit(slices.top, slices.bottom, image.height)));
+    let insets = slices.map(|slice| slice * scale);
+    let factor = fit(insets.left, insets.right, rect.width).min(fit(insets.top, insets.bottom, rect.height));
+    let insets = insets.map(|inset| inset * factor);
+
+    let source_x = [0.0, slices.left, image.width - slices.right, image.width];
+    let source_y = [0.0, slices.top, image.height - slices.bottom, image.height];
+    let x = [rect.x, rect.x + insets.left, rect.x + rect.width - insets.right, rect.x + rect.width];
+    let y = [rect.y, rect.y + insets.top, rect.y + rect.height - insets.bottom, rect.y + rect.height];
+
+    let mut rects = vec![];
+    for row in 0..3 {
+        for column in 0..3 {
+            let destination = Rect::new(x[column], y[row], x[column + 1] - x[column], y[row + 1] - y[row]);
+            let source = Rect::new(source_x[column], source_y[row], source_x[column + 1] - source_x[column], source_y[row + 1] - source_y[row]);
+            if destination.width > 0.0 && destination.height > 0.0 && source.width > 0.0 && source.height > 0.0 {
+                rects.push((destination, source));
+            }
+        }
+    }
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_rects_or_images_draw_nothing() {
+        assert!(image_rects(Rect::new(0.0, 0.0, 0.0, 10.0), Size::new(10.0, 10.0), ImageFit::Fill, 1.0).is_empty());
+        assert!(image_rects(Rect::new(0.0, 0.0, 10.0, 10.0), Size::new(0.0, 10.0), ImageFit::Fill, 1.0).is_empty());
+    }
+
+    #[test]
+    fn contain_and_cover_keep_the_aspect_ratio() {
+        let rect = Rect::new(0.0, 0.0, 200.0, 100.0);
+        let image = Size::new(50.0, 50.0);
+        assert_eq!(image_rects(rect, image, ImageFit::Contain, 1.0), vec![(Rect::new(50.0, 0.0, 100.0, 100.0), Rect::new(0.0, 0.0, 50.0, 50.0))]);
+        assert_eq!(image_rects(rect, image, ImageFit::Cover, 1.0), vec![(rect, Rect::new(0.0, 12.5, 50.0, 25.0))]);
+    }
+
+    #[test]
+    fn repeat_extends_the_source() {
+        let rect = Rect::new(0.0, 0.0, 100.0, 40.0);
+        assert_eq!(image_rects(rect, Size::new(10.0, 10.0), ImageFit::Repeat, 2.0), vec![(rect, Rect::new(0.0, 0.0, 50.0, 20.0))]);
+    }
+
+    #[test]
+    fn nine_slice_keeps_corners() {
+        let rects = nine_slice(Rect::new(0.0, 0.0, 100.0, 50.0), Size::new(30.0, 30.0), TRBL::all(10.0), 1.0);
+        assert_eq!(rects.len(), 9);
+        assert_eq!(rects[0], (Rect::new(0.0, 0.0, 10.0, 10.0), Rect::new(0.0, 0.0, 10.0, 10.0)));
+        assert_eq!(rects[4], (Rect::new(10.0, 10.0, 80.0, 30.0), Rect::new(10.0, 10.0, 10.0, 10.0)));
+        assert_eq!(rects[8], (Rect::new(90.0, 40.0, 10.0, 10.0), Rect::new(20.0, 20.0, 10.0, 10.0)));
+    }
+
+    #[test]
+    fn nine_slice_corners_shrink_to_fit() {
+        let rects = nine_slice(Rect::new(0.0, 0.0, 10.0, 40.0), Size::new(30.0, 30.0), TRBL::all(10.0), 1.0);
+        assert_eq!(rects[0].0, Rect::new(0.0, 0.0, 5.0, 5.0));
+        assert_eq!(rects.last().unwrap().0, Rect::new(5.0, 35.0, 5.0, 5.0));
+    }
+
+    #[test]
+    fn nine_slice_leaves_out_empty_parts() {
+        let rects = nine_slice(Rect::new(0.0, 0.0, 100.0, 100.0), Size::new(20.0, 20.0), TRBL::all(10.0), 1.0);
+        assert_eq!(rects.len(), 4);
+        let rects = nine_slice(Rect::new(0.0, 0.0, 100.0, 100.0), Size::new(20.0, 20.0), TRBL::new(30.0, 0.0, 0.0, 0.0), 1.0);
+        assert_eq!(rects, vec![(Rect::new(0.0, 0.0, 100.0, 20.0), Rect::new(0.0, 0.0, 20.0, 20.0))]);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod border;
 pub mod command;
+pub mod image;
 
-pub use self::command::Command;
+pub use self::command::{Command, Gradient, GlyphRun, Side};
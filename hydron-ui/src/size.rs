@@ -1,11 +1,11 @@
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Size {
-    pub width: usize,
-    pub height: usize,
+    pub width: f32,
+    pub height: f32,
 }
 
 impl Size {
-    pub fn new(width: usize, height: usize) -> Size {
+    pub fn new(width: f32, height: f32) -> Size {
         Size {
             width,
             height,
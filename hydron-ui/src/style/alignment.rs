@@ -0,0 +1,52 @@
+/// Distribution of free space along the major axis of a container.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Justify {
+    Start,
+    End,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+}
+
+/// Placement of a child along the minor axis of a container.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Align {
+    Start,
+    End,
+    Center,
+    Stretch,
+    Baseline,
+}
+
+/// Distribution of the lines of a wrapping container along its minor axis.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AlignContent {
+    Start,
+    End,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+    Stretch,
+}
+
+/// Whether children that do not fit along the major axis of a container
+/// are moved to a new line.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Wrap {
+    NoWrap,
+    Wrap,
+    WrapReverse,
+}
+
+/// Horizontal alignment of the lines of a `TextElement`. `Start` and `End`
+/// follow the direction of the element, `Left` and `Right` do not.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TextAlign {
+    Start,
+    End,
+    Left,
+    Right,
+    Center,
+}
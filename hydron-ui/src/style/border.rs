@@ -0,0 +1,54 @@
+use ::color::*;
+use super::length::*;
+use super::TRBL;
+
+/// How the line of a border is drawn.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BorderStyle {
+    /// No border, regardless of its width.
+    None,
+    Solid,
+    /// Dashes three times as long as the border is wide.
+    Dashed,
+    /// Square dots as large as the border is wide.
+    Dotted,
+}
+
+/// One side of the border of an element. The border sits between margin and
+/// padding and takes up room in layout.
+#[derive(Debug, Copy, Clone)]
+pub struct Border {
+    pub width: Length,
+    pub color: Rgba,
+    pub style: BorderStyle,
+}
+
+impl Border {
+    pub fn new(width: Length, color: Rgba, style: BorderStyle) -> Border {
+        Border {
+            width,
+            color,
+            style,
+        }
+    }
+
+    pub fn none() -> Border {
+        Border::new(Length::zero(), Rgba::new(0.0, 0.0, 0.0, 0.0), BorderStyle::None)
+    }
+
+    pub fn solid(width: Length, color: Rgba) -> Border {
+        Border::new(width, color, BorderStyle::Solid)
+    }
+}
+
+impl TRBL<Border> {
+    /// Widths of all sides with percentages taken of `base`, sides with
+    /// `BorderStyle::None` have no width.
+    pub fn resolve_widths(&self, base: f32, units: &Units) -> TRBL<f32> {
+        let resolve = |border: Border| match border.style {
+            BorderStyle::None => 0.0,
+            _ => border.width.resolve_or_zero(base, units),
+        };
+        TRBL::new(resolve(self.top), resolve(self.right), resolve(self.bottom), resolve(self.left))
+    }
+}
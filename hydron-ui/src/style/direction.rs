@@ -0,0 +1,19 @@
+/// Direction of horizontal flow, see `Style::direction`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Direction {
+    /// Left to right.
+    Ltr,
+    /// Right to left.
+    Rtl,
+}
+
+impl Direction {
+    /// Horizontal position of something `width` wide at `x` from the start
+    /// of a container `available` wide.
+    pub fn place(&self, x: f32, width: f32, available: f32) -> f32 {
+        match *self {
+            Direction::Ltr => x,
+            Direction::Rtl => available - x - width,
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use ::color::*;
+use ::std::ops::Range;
+use super::length::*;
+
+/// Most stops a renderer draws a gradient with at once, see `stop_chunks`.
+pub const MAX_GRADIENT_STOPS: usize = 8;
+
+/// Color at a position along a gradient, from 0 at the start to 1 at the
+/// end. Stops without a position are spread evenly between their
+/// neighbours.
+#[derive(Debug, Copy, Clone)]
+pub struct ColorStop {
+    pub color: Rgba,
+    pub position: Option<f32>,
+}
+
+impl ColorStop {
+    pub fn new(color: Rgba, position: f32) -> ColorStop {
+        ColorStop {
+            color,
+            position: Some(position),
+        }
+    }
+
+    pub fn auto(color: Rgba) -> ColorStop {
+        ColorStop {
+            color,
+            position: None,
+        }
+    }
+}
+
+/// Gradient along a line through the center of the box.
+#[derive(Debug, Clone)]
+pub struct LinearGradient {
+    /// Direction in degrees, 0 points up and 90 to the right. The line is
+    /// as long as needed for the corners to get the first and last color.
+    pub angle: f32,
+    /// Any number of stops, more than `MAX_GRADIENT_STOPS` are drawn in
+    /// parts, see `stop_chunks`.
+    pub stops: Vec<ColorStop>,
+}
+
+impl LinearGradient {
+    pub fn new(angle: f32, stops: Vec<ColorStop>) -> LinearGradient {
+        LinearGradient {
+            angle,
+            stops,
+        }
+    }
+}
+
+/// Circular gradient around a center.
+#[derive(Debug, Clone)]
+pub struct RadialGradient {
+    /// Center as a fraction of the width and height of the box.
+    pub center: (f32, f32),
+    /// Distance from the center the last color is reached at.
+    /// `Length::Auto` reaches the farthest corner, percentages are taken of
+    /// the distance to it.
+    pub radius: Length,
+    /// Any number of stops, more than `MAX_GRADIENT_STOPS` are drawn in
+    /// parts, see `stop_chunks`.
+    pub stops: Vec<ColorStop>,
+}
+
+impl RadialGradient {
+    pub fn new(stops: Vec<ColorStop>) -> RadialGradient {
+        RadialGradient {
+            center: (0.5, 0.5),
+            radius: Length::Auto,
+            stops,
+        }
+    }
+
+    pub fn with_center(mut self, x: f32, y: f32) -> RadialGradient {
+        self.center = (x, y);
+        self
+    }
+
+    pub fn with_radius(mut self, radius: Length) -> RadialGradient {
+        self.radius = radius;
+        self
+    }
+}
+
+/// Positions of all `stops`. Missing positions are spread evenly, the
+/// first and last default to 0 and 1, and no stop comes before the one
+/// preceding it.
+pub fn resolve_stops(stops: &Vec<ColorStop>) -> Vec<(f32, Rgba)> {
+    let count = stops.len();
+    let mut positions: Vec<Option<f32>> = stops.iter().map(|stop| stop.position).collect();
+    if count == 0 {
+        return vec![];
+    }
+    if positions[0].is_none() {
+        positions[0] = Some(0.0);
+    }
+    if positions[count - 1].is_none() {
+        positions[count - 1] = Some(1.0);
+    }
+
+    // no stop comes before the one preceding it
+    let mut previous: f32 = positions[0].unwrap();
+    for position in positions.iter_mut() {
+        if let Some(value) = *position {
+            previous = previous.max(value);
+            *position = Some(previous);
+        }
+    }
+
+    // spread stops without a position evenly between their neighbours
+    let mut ix = 0;
+    while ix < count {
+        if positions[ix].is_some() {
+            ix += 1;
+            continue;
+        }
+        let start = ix - 1;
+        let mut end = ix;
+        while positions[end].is_none() {
+            end += 1;
+        }
+        let (from, to) = (positions[start].unwrap(), positions[end].unwrap());
+        for missing in ix..end {
+            let fraction = (missing - start) as f32 / (end - start) as f32;
+            positions[missing] = Some(from + (to - from) * fraction);
+        }
+        ix = end;
+    }
+
+    positions.into_iter().zip(stops.iter()).map(|(position, stop)| (position.unwrap(), stop.color)).collect()
+}
+
+/// Ranges of at most `MAX_GRADIENT_STOPS` of `count` resolved stops, for
+/// renderers that draw a gradient in parts. Every part starts at the last
+/// stop of the one before, and is drawn from the position of its first stop
+/// up to the position of its last stop, the first and the last part also
+/// before and after that.
+pub fn stop_chunks(count: usize) -> Vec<Range<usize>> {
+    let mut chunks = vec![];
+    let mut start = 0;
+    while start + 1 < count {
+        let end = (start + MAX_GRADIENT_STOPS).min(count);
+        chunks.push(start..end);
+        start = end - 1;
+    }
+    if chunks.is_empty() && count > 0 {
+        chunks.push(0..count);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(value: f64) -> Rgba {
+        Rgba::new(value, value, value, 1.0)
+    }
+
+    fn positions(stops: &Vec<ColorStop>) -> Vec<f32> {
+        resolve_stops(stops).iter().map(|stop| stop.0).collect()
+    }
+
+    #[test]
+    fn no_stops_resolve_to_nothing() {
+        assert!(resolve_stops(&vec![]).is_empty());
+    }
+
+    #[test]
+    fn first_and_last_default_to_the_ends() {
+        assert_eq!(positions(&vec![ColorStop::auto(gray(0.0)), ColorStop::auto(gray(1.0))]), vec![0.0, 1.0]);
+        assert_eq!(positions(&vec![ColorStop::auto(gray(0.0))]), vec![0.0]);
+    }
+
+    #[test]
+    fn missing_positions_are_spread_evenly() {
+        let stops = vec![
+            ColorStop::new(gray(0.0), 0.2),
+            ColorStop::auto(gray(0.5)),
+            ColorStop::auto(gray(0.5)),
+            ColorStop::new(gray(1.0), 0.8),
+        ];
+        let resolved = positions(&stops);
+        assert!((resolved[1] - 0.4).abs() < 1e-6 && (resolved[2] - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stops_never_go_back() {
+        let stops = vec![
+            ColorStop::new(gray(0.0), 0.5),
+            ColorStop::new(gray(0.5), 0.2),
+            ColorStop::auto(gray(1.0)),
+        ];
+        assert_eq!(positions(&stops), vec![0.5, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn long_stop_lists_keep_hard_stops() {
+        let mut stops: Vec<ColorStop> = (0..20).map(|ix| ColorStop::auto(gray(ix as f64 / 20.0))).collect();
+        stops[10] = ColorStop::new(gray(0.0), 0.5);
+        stops[11] = ColorStop::new(gray(1.0), 0.5);
+        let resolved = resolve_stops(&stops);
+        assert_eq!(resolved.len(), 20);
+        assert_eq!(resolved[10], (0.5, gray(0.0)));
+        assert_eq!(resolved[11], (0.5, gray(1.0)));
+    }
+
+    #[test]
+    fn chunks_share_their_first_stop_with_the_chunk_before() {
+        assert!(stop_chunks(0).is_empty());
+        assert_eq!(stop_chunks(1), vec![0..1]);
+        assert_eq!(stop_chunks(MAX_GRADIENT_STOPS), vec![0..MAX_GRADIENT_STOPS]);
+        assert_eq!(stop_chunks(MAX_GRADIENT_STOPS + 1), vec![0..MAX_GRADIENT_STOPS, MAX_GRADIENT_STOPS - 1..MAX_GRADIENT_STOPS + 1]);
+        let chunks = stop_chunks(20);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= MAX_GRADIENT_STOPS));
+        assert!(chunks.windows(2).all(|pair| pair[1].start == pair[0].end - 1));
+        assert_eq!((chunks[0].start, chunks[chunks.len() - 1].end), (0, 20));
+    }
+}
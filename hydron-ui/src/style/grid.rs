@@ -0,0 +1,29 @@
+/// Tracks of a `GridElement` covered by a child, starting at the zero based
+/// track `start`. A placement always covers at least one track.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GridPlacement {
+    start: usize,
+    span: usize,
+}
+
+impl GridPlacement {
+    pub fn new(start: usize, span: usize) -> GridPlacement {
+        GridPlacement {
+            start,
+            span: span.max(1),
+        }
+    }
+
+    pub fn at(start: usize) -> GridPlacement {
+        GridPlacement::new(start, 1)
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Number of tracks covered, at least 1.
+    pub fn span(&self) -> usize {
+        self.span
+    }
+}
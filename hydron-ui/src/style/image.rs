@@ -0,0 +1,22 @@
+use super::TRBL;
+
+/// How an image fills the box it is drawn into. One pixel of the image is
+/// one logical unit at its own size.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImageFit {
+    /// Stretches the image to the size of the box.
+    Fill,
+    /// Scales the image to the largest size that fits into the box while
+    /// keeping its aspect ratio, and centers it.
+    Contain,
+    /// Scales the image to the smallest size that covers the box while
+    /// keeping its aspect ratio, and centers it. Parts outside of the box
+    /// are cut off.
+    Cover,
+    /// Tiles the image at its own size, starting at the top left corner.
+    Repeat,
+    /// Cuts the image into nine parts at the given distances in pixels from
+    /// its edges. Corners keep their own size, edges and center stretch to
+    /// fill the box.
+    NineSlice(TRBL<f32>),
+}
@@ -0,0 +1,57 @@
+use ::constraint::UNBOUNDED;
+
+/// Font size of the root element, in logical units.
+pub const DEFAULT_FONT_SIZE: f32 = 16.0;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Length {
+    /// Device pixels.
+    Px(f32),
+    /// Logical units, device pixels divided by the scale factor.
+    Dp(f32),
+    /// Multiple of the font size of the element.
+    Em(f32),
+    /// Percentage of the size the parent makes available to the element.
+    Percent(f32),
+    /// Left to the layout, what that means depends on the property.
+    Auto,
+}
+
+/// What relative lengths are resolved against.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Units {
+    pub scale_factor: f32,
+    pub font_size: f32,
+}
+
+impl Units {
+    pub fn new(scale_factor: f32, font_size: f32) -> Units {
+        Units {
+            scale_factor,
+            font_size,
+        }
+    }
+}
+
+impl Length {
+    pub fn zero() -> Length {
+        Length::Dp(0.0)
+    }
+
+    /// Length in logical units, with percentages taken of `base`. `Auto` and
+    /// percentages of an `UNBOUNDED` base have no length.
+    pub fn resolve(&self, base: f32, units: &Units) -> Option<f32> {
+        match *self {
+            Length::Px(px) => Some(px / units.scale_factor),
+            Length::Dp(dp) => Some(dp),
+            Length::Em(em) => Some(em * units.font_size),
+            Length::Percent(percent) if base < UNBOUNDED => Some(base * percent / 100.0),
+            Length::Percent(_) | Length::Auto => None,
+        }
+    }
+
+    /// Like `resolve`, but lengths that cannot be resolved are zero.
+    pub fn resolve_or_zero(&self, base: f32, units: &Units) -> f32 {
+        self.resolve(base, units).unwrap_or(0.0)
+    }
+}
@@ -1,11 +1,33 @@
 use ::color::*;
+use ::image_id::*;
+use ::std::ops::Add;
 
-#[derive(Debug)]
+mod alignment;
+mod border;
+mod direction;
+mod gradient;
+mod grid;
+mod image;
+mod length;
+mod position;
+mod shadow;
+
+pub use self::alignment::*;
+pub use self::border::*;
+pub use self::direction::*;
+pub use self::gradient::*;
+pub use self::grid::*;
+pub use self::image::*;
+pub use self::length::*;
+pub use self::position::*;
+pub use self::shadow::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct TRBL<T> {
-    top: T,
-    right: T,
-    bottom: T,
-    left: T,
+    pub top: T,
+    pub right: T,
+    pub bottom: T,
+    pub left: T,
 }
 
 impl<T> TRBL<T> {
@@ -19,36 +41,216 @@ impl<T> TRBL<T> {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub enum Border {
-    None,
-    Color(Rgba),
+impl<T: Copy> TRBL<T> {
+    pub fn all(value: T) -> TRBL<T> {
+        TRBL::new(value, value, value, value)
+    }
+
+    /// Swaps the left and right side.
+    pub fn mirror(&self) -> TRBL<T> {
+        TRBL::new(self.top, self.left, self.bottom, self.right)
+    }
+
+    pub fn map<U, F: Fn(T) -> U>(&self, f: F) -> TRBL<U> {
+        TRBL::new(f(self.top), f(self.right), f(self.bottom), f(self.left))
+    }
+}
+
+impl TRBL<Length> {
+    /// Resolves all sides with percentages taken of `base`, lengths that
+    /// cannot be resolved are zero.
+    pub fn resolve(&self, base: f32, units: &Units) -> TRBL<f32> {
+        let resolve = |length: Length| length.resolve_or_zero(base, units);
+        TRBL::new(resolve(self.top), resolve(self.right), resolve(self.bottom), resolve(self.left))
+    }
+}
+
+impl Add for TRBL<f32> {
+    type Output = TRBL<f32>;
+
+    fn add(self, other: TRBL<f32>) -> TRBL<f32> {
+        TRBL::new(self.top + other.top, self.right + other.right, self.bottom + other.bottom, self.left + other.left)
+    }
+}
+
+impl TRBL<f32> {
+    pub fn horizontal(&self) -> f32 {
+        self.left + self.right
+    }
+
+    pub fn vertical(&self) -> f32 {
+        self.top + self.bottom
+    }
+}
+
+/// A value for each corner of a box, e.g. a radius.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Corners<T> {
+    pub top_left: T,
+    pub top_right: T,
+    pub bottom_right: T,
+    pub bottom_left: T,
+}
+
+impl<T> Corners<T> {
+    pub fn new(top_left: T, top_right: T, bottom_right: T, bottom_left: T) -> Corners<T> {
+        Corners {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        }
+    }
+}
+
+impl<T: Copy> Corners<T> {
+    pub fn all(value: T) -> Corners<T> {
+        Corners::new(value, value, value, value)
+    }
+
+    /// Swaps the left and right corners.
+    pub fn mirror(&self) -> Corners<T> {
+        Corners::new(self.top_right, self.top_left, self.bottom_left, self.bottom_right)
+    }
+
+    pub fn map<U, F: Fn(T) -> U>(&self, f: F) -> Corners<U> {
+        Corners::new(f(self.top_left), f(self.top_right), f(self.bottom_right), f(self.bottom_left))
+    }
+}
+
+impl Corners<Length> {
+    /// Resolves all corners with percentages taken of `base`, lengths that
+    /// cannot be resolved are zero.
+    pub fn resolve(&self, base: f32, units: &Units) -> Corners<f32> {
+        self.map(|length| length.resolve_or_zero(base, units))
+    }
+}
+
+impl Corners<f32> {
+    pub fn is_zero(&self) -> bool {
+        *self == Corners::all(0.0)
+    }
+
+    /// Scales all radii down by the same factor until radii that share an
+    /// edge of a `width` by `height` box fit onto it.
+    pub fn fit(&self, width: f32, height: f32) -> Corners<f32> {
+        let ratio = |length: f32, a: f32, b: f32| if a + b > length { length / (a + b) } else { 1.0 };
+        let factor = ratio(width, self.top_left, self.top_right)
+            .min(ratio(width, self.bottom_left, self.bottom_right))
+            .min(ratio(height, self.top_left, self.bottom_left))
+            .min(ratio(height, self.top_right, self.bottom_right))
+            .max(0.0);
+        self.map(|radius| radius * factor)
+    }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub enum Background {
     None,
     Color(Rgba),
-    Image(usize),
+    Image(ImageId, ImageFit),
+    LinearGradient(LinearGradient),
+    RadialGradient(RadialGradient),
 }
 
 #[derive(Debug)]
 pub struct Style {
     pub background: Background,
+    /// In `Direction::Rtl` the left and right side are swapped like padding
+    /// and margin.
     pub border: TRBL<Border>,
+    pub padding: TRBL<Length>,
+    pub margin: TRBL<Length>,
+    /// Size of the element including its border and padding, but not its
+    /// margin. The size always stays within the constraint of the parent,
+    /// percentages are taken of the maximum of that constraint.
+    pub width: Length,
+    pub height: Length,
+    pub min_width: Length,
+    pub min_height: Length,
+    pub max_width: Length,
+    pub max_height: Length,
+    /// Direction children are placed in horizontally. `None` inherits the
+    /// direction of the parent, the root defaults to `Direction::Ltr`. In
+    /// `Direction::Rtl` the left and right side of padding and margin are
+    /// swapped, so `left` is always the start side.
+    pub direction: Option<Direction>,
+    /// Radius of each corner of the border box. Percentages are taken of the
+    /// shorter side of the box, radii that do not fit are scaled down. Left
+    /// and right corners are swapped in `Direction::Rtl` like the border.
+    pub border_radius: Corners<Length>,
+    /// Shadows painted below the background, or above it if inset. The
+    /// first shadow is on top.
+    pub box_shadow: Vec<BoxShadow>,
+    /// Opacity of the element and its children, from 0 to 1. They are drawn
+    /// as one layer that is then blended with this opacity.
+    pub opacity: f32,
+    /// Ratio of width to height the element keeps, including its border and
+    /// padding.
+    /// The element takes the largest size with that ratio its constraint and
+    /// size properties allow, or its max intrinsic width if neither axis is
+    /// bounded.
+    pub aspect_ratio: Option<f32>,
+    /// Size `Length::Em` is relative to. `None` inherits the font size of
+    /// the parent, `Length::Em` and `Length::Percent` are relative to it.
+    pub font_size: Option<Length>,
+    pub flex_grow: f32,
+    pub flex_shrink: f32,
+    /// Size along the major axis of a `FlexElement` before free space is
+    /// distributed. `None` or `Length::Auto` uses the size the child wants
+    /// to be, percentages are taken of the maximum length of the container.
+    pub flex_basis: Option<Length>,
+    /// Overrides the alignment the parent container uses for this child.
+    pub align_self: Option<Align>,
+    /// Row of a `GridElement` the child is placed in. `None` places the
+    /// child in the next free cell.
+    pub grid_row: Option<GridPlacement>,
+    /// Column of a `GridElement` the child is placed in. `None` places the
+    /// child in the next free cell.
+    pub grid_column: Option<GridPlacement>,
+    /// Horizontal alignment of the child inside a `StackElement`.
+    pub justify_self: Option<Align>,
+    pub position: Position,
+    /// Distance from the edges of the containing block for
+    /// `Position::Absolute`, or shift for `Position::Relative`.
+    pub offsets: TRBL<Option<Length>>,
 }
 
 impl Style {
     pub fn empty() -> Style {
         Style {
             background: Background::None,
-            border: TRBL::new(Border::None, Border::None, Border::None, Border::None),
+            border: TRBL::all(Border::none()),
+            padding: TRBL::all(Length::zero()),
+            margin: TRBL::all(Length::zero()),
+            width: Length::Auto,
+            height: Length::Auto,
+            min_width: Length::Auto,
+            min_height: Length::Auto,
+            max_width: Length::Auto,
+            max_height: Length::Auto,
+            direction: None,
+            border_radius: Corners::all(Length::zero()),
+            box_shadow: vec![],
+            opacity: 1.0,
+            aspect_ratio: None,
+            font_size: None,
+            flex_grow: 0.0,
+            flex_shrink: 1.0,
+            flex_basis: None,
+            align_self: None,
+            grid_row: None,
+            grid_column: None,
+            justify_self: None,
+            position: Position::Static,
+            offsets: TRBL::all(None),
         }
     }
     pub fn new(background: Background, border: TRBL<Border>) -> Style {
         Style {
             background,
             border,
+            ..Style::empty()
         }
     }
 }
@@ -0,0 +1,11 @@
+/// How an element is placed relative to its parent.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Position {
+    /// Placed by the layout of its parent.
+    Static,
+    /// Placed by the layout of its parent, then shifted by its offsets.
+    Relative,
+    /// Taken out of the layout of its parent and placed by its offsets
+    /// relative to the nearest ancestor that is not `Static`.
+    Absolute,
+}
@@ -0,0 +1,36 @@
+use ::color::*;
+use super::length::*;
+
+/// Shadow cast by the border box of an element, or into its padding box if
+/// `inset` is set.
+#[derive(Debug, Copy, Clone)]
+pub struct BoxShadow {
+    pub offset_x: Length,
+    pub offset_y: Length,
+    /// Radius of the blur, twice the standard deviation of the gaussian.
+    pub blur: Length,
+    /// Grows the shadow before it is blurred, or shrinks it if negative.
+    pub spread: Length,
+    pub color: Rgba,
+    pub inset: bool,
+}
+
+impl BoxShadow {
+    pub fn new(offset_x: Length, offset_y: Length, blur: Length, spread: Length, color: Rgba) -> BoxShadow {
+        BoxShadow {
+            offset_x,
+            offset_y,
+            blur,
+            spread,
+            color,
+            inset: false,
+        }
+    }
+
+    pub fn inset(offset_x: Length, offset_y: Length, blur: Length, spread: Length, color: Rgba) -> BoxShadow {
+        BoxShadow {
+            inset: true,
+            ..BoxShadow::new(offset_x, offset_y, blur, spread, color)
+        }
+    }
+}
@@ -1,58 +1,561 @@
 use super::rendering::command::*;
+use super::rendering::border::*;
 use super::constraint::*;
+use super::element_id::*;
+use super::error::*;
 use super::layout::*;
 use super::size::*;
 use super::rect::*;
 use super::style::*;
+use super::text::*;
+use ::std::cell::{Cell, RefCell};
+use ::std::iter::Cloned;
 use ::std::mem::swap;
+use ::std::slice::Iter;
 
 pub struct LayoutContext {
+    elements: Vec<Option<Box<LayoutElement>>>,
+    children: Vec<Vec<ElementId>>,
     data: Vec<Rect>,
+    styles: Vec<Style>,
+    font_sizes: Vec<f32>,
+    directions: Vec<Direction>,
+    margins: Vec<TRBL<f32>>,
+    borders: Vec<TRBL<f32>>,
+    paddings: Vec<TRBL<f32>>,
+    offsets: Vec<TRBL<Option<f32>>>,
+    baselines: Vec<Option<f32>>,
+    intrinsics: Vec<RefCell<Vec<(Intrinsic, f32, f32)>>>,
+    generations: Vec<usize>,
+    laid_out_children: usize,
+    direction: Direction,
+    units: Cell<Units>,
+    scale_factor: f32,
+    fonts: Fonts,
 }
 
 impl LayoutContext {
     pub fn new() -> LayoutContext {
         LayoutContext {
+            elements: vec![],
+            children: vec![],
             data: vec![],
+            styles: vec![],
+            font_sizes: vec![],
+            directions: vec![],
+            margins: vec![],
+            borders: vec![],
+            paddings: vec![],
+            offsets: vec![],
+            baselines: vec![],
+            intrinsics: vec![],
+            generations: vec![],
+            laid_out_children: 0,
+            direction: Direction::Ltr,
+            units: Cell::new(Units::new(1.0, DEFAULT_FONT_SIZE)),
+            scale_factor: 1.0,
+            fonts: Fonts::new(),
         }
     }
 
-    fn insert(&mut self, rect: Rect) {
+    /// Fonts text is measured with.
+    pub fn get_fonts(&self) -> &Fonts {
+        &self.fonts
+    }
+
+    /// Number of `LayoutResult::LayoutChild` requests the element currently
+    /// being laid out already got an answer for in this pass.
+    pub fn laid_out_children(&self) -> usize {
+        self.laid_out_children
+    }
+
+    /// Direction of the element currently being laid out. Elements that
+    /// place children horizontally mirror their placement in
+    /// `Direction::Rtl`.
+    pub fn get_direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Scale factor and font size of the element currently being laid out
+    /// or measured.
+    pub fn get_units(&self) -> Units {
+        self.units.get()
+    }
+
+    fn insert(&mut self, rect: Rect) -> ElementId {
+        let index = self.data.len();
         self.data.push(rect);
+        self.styles.push(Style::empty());
+        self.font_sizes.push(DEFAULT_FONT_SIZE);
+        self.directions.push(Direction::Ltr);
+        self.margins.push(TRBL::all(0.0));
+        self.borders.push(TRBL::all(0.0));
+        self.paddings.push(TRBL::all(0.0));
+        self.offsets.push(TRBL::all(None));
+        self.baselines.push(None);
+        self.intrinsics.push(RefCell::new(vec![]));
+        self.generations.push(0);
+        ElementId::new(index, 0)
+    }
+
+    fn reuse(&mut self, index: usize) -> ElementId {
+        ElementId::new(index, self.generations[index])
+    }
+
+    fn reset(&mut self, index: usize) {
+        self.data[index] = Rect::new(0.0, 0.0, 0.0, 0.0);
+        self.styles[index] = Style::empty();
+        self.font_sizes[index] = DEFAULT_FONT_SIZE;
+        self.directions[index] = Direction::Ltr;
+        self.margins[index] = TRBL::all(0.0);
+        self.borders[index] = TRBL::all(0.0);
+        self.paddings[index] = TRBL::all(0.0);
+        self.offsets[index] = TRBL::all(None);
+        self.baselines[index] = None;
+        self.forget_intrinsics(index);
+    }
+
+    /// Drops the intrinsic sizes remembered for the element, they are
+    /// computed again when asked for.
+    fn forget_intrinsics(&self, index: usize) {
+        self.intrinsics[index].borrow_mut().clear();
+    }
+
+    fn kill(&mut self, index: usize) {
+        self.generations[index] += 1;
+    }
+
+    fn check(&self, id: ElementId) -> Result<usize, SurfaceError> {
+        match self.generations.get(id.index()) {
+            Some(generation) if *generation == id.generation() => Ok(id.index()),
+            _ => Err(SurfaceError::DeadElement(id)),
+        }
     }
 
     fn get_rect(&self, index: usize) -> Rect {
         self.data[index]
     }
 
-    fn set_size(&mut self, index: usize, width: usize, height: usize) {
+    fn set_size(&mut self, index: usize, width: f32, height: f32) {
         self.data[index].width = width;
         self.data[index].height = height;
     }
 
-    pub fn get_child_size(&mut self, index: usize) -> Size {
-        let rect = self.data[index];
-        Size::new(rect.width, rect.height)
-    }
-
-    pub fn position_child(&mut self, index: usize, x: usize, y: usize) {
+    fn set_position(&mut self, index: usize, x: f32, y: f32) {
         self.data[index].x = x;
         self.data[index].y = y;
     }
+
+    pub fn get_child_size(&self, id: ElementId) -> Result<Size, SurfaceError> {
+        let rect = self.data[self.check(id)?];
+        Ok(Size::new(rect.width, rect.height))
+    }
+
+    /// Distance from the top of the child to its first baseline. Children
+    /// without text use their bottom edge.
+    pub fn get_child_baseline(&self, id: ElementId) -> Result<f32, SurfaceError> {
+        let index = self.check(id)?;
+        Ok(self.baselines[index].unwrap_or(self.data[index].height))
+    }
+
+    pub fn get_child_style(&self, id: ElementId) -> Result<&Style, SurfaceError> {
+        Ok(&self.styles[self.check(id)?])
+    }
+
+    /// Scale factor and font size to resolve the lengths in the style of the
+    /// child with.
+    pub fn get_child_units(&self, id: ElementId) -> Result<Units, SurfaceError> {
+        Ok(self.units(self.check(id)?))
+    }
+
+    fn units(&self, index: usize) -> Units {
+        Units::new(self.scale_factor, self.font_sizes[index])
+    }
+
+    /// Margin, border widths and padding of the child, with percentages
+    /// taken of `base` and the sides swapped in `Direction::Rtl`.
+    pub fn get_child_insets(&self, id: ElementId, base: f32) -> Result<(TRBL<f32>, TRBL<f32>, TRBL<f32>), SurfaceError> {
+        Ok(self.resolve_insets(self.check(id)?, base))
+    }
+
+    /// Margin, border widths and padding of the element, with percentages
+    /// taken of `base` and the sides swapped in `Direction::Rtl`.
+    fn resolve_insets(&self, index: usize, base: f32) -> (TRBL<f32>, TRBL<f32>, TRBL<f32>) {
+        let units = self.units(index);
+        let style = &self.styles[index];
+        let margin = style.margin.resolve(base, &units);
+        let border = style.border.resolve_widths(base, &units);
+        let padding = style.padding.resolve(base, &units);
+        match self.directions[index] {
+            Direction::Ltr => (margin, border, padding),
+            Direction::Rtl => (margin.mirror(), border.mirror(), padding.mirror()),
+        }
+    }
+
+    /// Width or height of the element with its lower and upper limit, with
+    /// percentages taken of `base`.
+    fn resolve_size(&self, index: usize, horizontal: bool, base: f32) -> (Option<f32>, f32, f32) {
+        let units = self.units(index);
+        let style = &self.styles[index];
+        let (size, min, max) = if horizontal {
+            (style.width, style.min_width, style.max_width)
+        } else {
+            (style.height, style.min_height, style.max_height)
+        };
+        (size.resolve(base, &units), min.resolve_or_zero(base, &units), max.resolve(base, &units).unwrap_or(UNBOUNDED))
+    }
+
+    /// Narrows `constraint` to the size properties and aspect ratio of the
+    /// element, without leaving it.
+    fn size_constraint(&self, index: usize, constraint: Constraint, base_width: f32, base_height: f32) -> Result<Constraint, SurfaceError> {
+        let (min_width, max_width) = narrow(constraint.min_width, constraint.max_width, self.resolve_size(index, true, base_width));
+        let (min_height, max_height) = narrow(constraint.min_height, constraint.max_height, self.resolve_size(index, false, base_height));
+        let constraint = Constraint::new(min_width, max_width, min_height, max_height);
+
+        let ratio = match self.styles[index].aspect_ratio {
+            Some(ratio) if ratio > 0.0 => ratio,
+            _ => return Ok(constraint),
+        };
+        let size = match constraint.fit_aspect_ratio(ratio) {
+            Some(size) => size,
+            None => {
+                let (margin, _, _) = self.resolve_insets(index, UNBOUNDED);
+                let width = self.intrinsic(index, Intrinsic::MaxWidth, UNBOUNDED)? - margin.horizontal();
+                constraint.constrain(Size::new(width, width / ratio))
+            },
+        };
+        Ok(Constraint::fixed(size.width, size.height))
+    }
+
+    /// Smallest width the child can take without overflowing when it is
+    /// `height` high, including its margin, border and padding.
+    pub fn get_child_min_intrinsic_width(&self, id: ElementId, height: f32) -> Result<f32, SurfaceError> {
+        self.intrinsic(self.check(id)?, Intrinsic::MinWidth, height)
+    }
+
+    /// Width the child takes when it has all the room it wants, including its
+    /// margin, border and padding.
+    pub fn get_child_max_intrinsic_width(&self, id: ElementId, height: f32) -> Result<f32, SurfaceError> {
+        self.intrinsic(self.check(id)?, Intrinsic::MaxWidth, height)
+    }
+
+    pub fn get_child_min_intrinsic_height(&self, id: ElementId, width: f32) -> Result<f32, SurfaceError> {
+        self.intrinsic(self.check(id)?, Intrinsic::MinHeight, width)
+    }
+
+    pub fn get_child_max_intrinsic_height(&self, id: ElementId, width: f32) -> Result<f32, SurfaceError> {
+        self.intrinsic(self.check(id)?, Intrinsic::MaxHeight, width)
+    }
+
+    /// Intrinsic size of the element including its insets. Percentages are
+    /// not known at this point and count as zero or `Length::Auto`. Sizes are
+    /// remembered until the element is marked dirty or laid out, so elements
+    /// that ask their children more than once don't measure the subtree
+    /// again.
+    fn intrinsic(&self, index: usize, intrinsic: Intrinsic, cross: f32) -> Result<f32, SurfaceError> {
+        let known = self.intrinsics[index].borrow().iter()
+            .find(|known| known.0 == intrinsic && known.1 == cross)
+            .map(|known| known.2);
+        if let Some(size) = known {
+            return Ok(size);
+        }
+
+        let size = self.measure_intrinsic(index, intrinsic, cross)?;
+        let mut intrinsics = self.intrinsics[index].borrow_mut();
+        if intrinsics.len() == MAX_INTRINSICS {
+            intrinsics.remove(0);
+        }
+        intrinsics.push((intrinsic, cross, size));
+        Ok(size)
+    }
+
+    fn measure_intrinsic(&self, index: usize, intrinsic: Intrinsic, cross: f32) -> Result<f32, SurfaceError> {
+        let (margin, border, padding) = self.resolve_insets(index, UNBOUNDED);
+        let padding = border + padding;
+        let horizontal = match intrinsic {
+            Intrinsic::MinWidth | Intrinsic::MaxWidth => true,
+            Intrinsic::MinHeight | Intrinsic::MaxHeight => false,
+        };
+        let (margin_major, padding_major, margin_minor, padding_minor) = if horizontal {
+            (margin.horizontal(), padding.horizontal(), margin.vertical(), padding.vertical())
+        } else {
+            (margin.vertical(), padding.vertical(), margin.horizontal(), padding.horizontal())
+        };
+
+        // a fixed size on the other axis replaces the one asked with
+        let cross = match self.resolve_size(index, !horizontal, UNBOUNDED) {
+            (Some(size), min, max) => size.min(max).max(min),
+            (None, _, _) => (cross - margin_minor).max(0.0),
+        };
+
+        // with an aspect ratio the size follows from the other axis
+        let ratio = match self.styles[index].aspect_ratio {
+            Some(ratio) if ratio > 0.0 && cross < UNBOUNDED => Some(if horizontal { ratio } else { 1.0 / ratio }),
+            _ => None,
+        };
+        let outer_cross = cross;
+        let cross = (cross - padding_minor).max(0.0);
+
+        let (size, min, max) = self.resolve_size(index, horizontal, UNBOUNDED);
+        let content = match (size, ratio) {
+            (Some(size), _) => size,
+            (None, Some(ratio)) => outer_cross * ratio,
+            (None, None) => {
+                let element = self.elements[index].as_ref().unwrap();
+                let children = self.flow_children(index);
+                let outer_units = self.units.replace(self.units(index));
+                let content = match intrinsic {
+                    Intrinsic::MinWidth => element.min_intrinsic_width(cross, self, &children),
+                    Intrinsic::MaxWidth => element.max_intrinsic_width(cross, self, &children),
+                    Intrinsic::MinHeight => element.min_intrinsic_height(cross, self, &children),
+                    Intrinsic::MaxHeight => element.max_intrinsic_height(cross, self, &children),
+                };
+                self.units.set(outer_units);
+                content? + padding_major
+            },
+        };
+        Ok(content.min(max).max(min) + margin_major)
+    }
+
+    /// Children that take part in the layout of their parent, leaving out
+    /// absolutely positioned ones.
+    fn flow_children(&self, index: usize) -> Vec<ElementId> {
+        self.children[index].iter()
+            .filter(|child| self.styles[child.index()].position != Position::Absolute)
+            .cloned()
+            .collect()
+    }
+
+    pub fn position_child(&mut self, id: ElementId, x: f32, y: f32) -> Result<(), SurfaceError> {
+        let index = self.check(id)?;
+        self.set_position(index, x, y);
+        Ok(())
+    }
+}
+
+/// Number of intrinsic sizes remembered per element.
+const MAX_INTRINSICS: usize = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Intrinsic {
+    MinWidth,
+    MaxWidth,
+    MinHeight,
+    MaxHeight,
+}
+
+/// Narrows `min..max` to the resolved `(size, min, max)` of an element. A
+/// minimum wins over a maximum, the outer range wins over both.
+fn narrow(min: f32, max: f32, limits: (Option<f32>, f32, f32)) -> (f32, f32) {
+    let clamp = |value: f32| value.max(min).min(max);
+    let (size, size_min, size_max) = limits;
+    match size {
+        Some(size) => {
+            let size = clamp(size.min(size_max).max(size_min));
+            (size, size)
+        },
+        None => {
+            let low = clamp(size_min);
+            (low, clamp(size_max).max(low))
+        },
+    }
 }
 
 pub struct RenderContext {
     commands: Vec<Command>,
+    border: TRBL<f32>,
+    padding: TRBL<f32>,
+    radii: Corners<f32>,
+    units: Units,
+    direction: Direction,
+    scale_factor: f32,
 }
 
 impl RenderContext {
     pub fn new() -> RenderContext {
         RenderContext {
             commands: vec![],
+            border: TRBL::all(0.0),
+            padding: TRBL::all(0.0),
+            radii: Corners::all(0.0),
+            units: Units::new(1.0, DEFAULT_FONT_SIZE),
+            direction: Direction::Ltr,
+            scale_factor: 1.0,
+        }
+    }
+
+    /// Border widths of the element currently being painted, already
+    /// swapped in `Direction::Rtl`.
+    pub fn get_border(&self) -> TRBL<f32> {
+        self.border
+    }
+
+    /// Padding of the element currently being painted, already swapped in
+    /// `Direction::Rtl`.
+    pub fn get_padding(&self) -> TRBL<f32> {
+        self.padding
+    }
+
+    /// Corner radii of the border box of the element currently being
+    /// painted, already fitted to the box.
+    pub fn get_radii(&self) -> Corners<f32> {
+        self.radii
+    }
+
+    /// Fills the border box `rect` of the element currently being painted,
+    /// following its corner radii.
+    pub fn draw_background(&mut self, rect: Rect, background: &Background) {
+        let radii = self.radii;
+        let gradient = match *background {
+            Background::Color(color) if radii.is_zero() => return self.add_command(Command::DrawRect(rect, color)),
+            Background::Color(color) => return self.add_command(Command::DrawRoundedRect(rect, radii, color)),
+            Background::LinearGradient(ref gradient) => linear_gradient(rect, gradient),
+            Background::RadialGradient(ref gradient) => radial_gradient(rect, gradient, &self.units),
+            Background::Image(image, fit) => return self.add_command(Command::DrawImage { rect, radii, image, fit, scale: 1.0 }),
+            Background::None => return,
+        };
+        if gradient.get_stops().is_empty() {
+            return;
+        }
+        self.add_command(Command::DrawGradient(rect, radii, gradient));
+    }
+
+    /// Draws `border` of the element currently being painted along the edges
+    /// of its border box `rect`. With rounded corners a border with the same
+    /// color and solid line on all sides is drawn as one ring, any other
+    /// border as one part of the ring per side.
+    pub fn draw_border(&mut self, rect: Rect, border: &TRBL<Border>) {
+        let border = match self.direction {
+            Direction::Ltr => *border,
+            Direction::Rtl => border.mirror(),
+        };
+
+        if !self.radii.is_zero() {
+            let (radii, widths) = (self.radii, self.border);
+            let sides: Vec<(Side, Border, f32)> = vec![
+                (Side::Top, border.top, widths.top),
+                (Side::Right, border.right, widths.right),
+                (Side::Bottom, border.bottom, widths.bottom),
+                (Side::Left, border.left, widths.left),
+            ].into_iter().filter(|&(_, border, width)| border.style != BorderStyle::None && width > 0.0).collect();
+
+            let first = match sides.first() {
+                Some(&(_, border, _)) => border,
+                None => return,
+            };
+            let uniform = sides.iter().all(|&(_, border, _)| {
+                border.style == BorderStyle::Solid && border.color == first.color
+            });
+            if uniform {
+                return self.add_command(Command::DrawRoundedBorder { rect, radii, widths, side: None, color: first.color, dash: None });
+            }
+            for (side, border, width) in sides {
+                let dash = dash_pattern(border.style, width);
+                self.add_command(Command::DrawRoundedBorder { rect, radii, widths, side: Some(side), color: border.color, dash });
+            }
+            return;
+        }
+
+        for (rect, color) in border_rects(rect, self.border, &border) {
+            self.add_command(Command::DrawRect(rect, color));
+        }
+    }
+
+    /// Draws either the outer or the inset `shadows` of the element currently
+    /// being painted, with the border box `rect`.
+    pub fn draw_shadows(&mut self, rect: Rect, shadows: &Vec<BoxShadow>, inset: bool) {
+        let units = self.units;
+        let resolve = |length: Length| length.resolve_or_zero(UNBOUNDED, &units);
+        let padding_box = rect.inset(self.border);
+        let inner_radii = inner_radii(self.radii, self.border);
+
+        // the first shadow is on top, so it is drawn last
+        for shadow in shadows.iter().rev().filter(|shadow| shadow.inset == inset) {
+            let (x, y) = (resolve(shadow.offset_x), resolve(shadow.offset_y));
+            let (blur, spread) = (resolve(shadow.blur).max(0.0), resolve(shadow.spread));
+            let command = if inset {
+                let shape = Rect::new(padding_box.x + x, padding_box.y + y, padding_box.width, padding_box.height).inset(TRBL::all(spread));
+                Command::DrawInsetShadow {
+                    rect: padding_box,
+                    radii: inner_radii,
+                    shape,
+                    shape_radii: grow_radii(inner_radii, -spread),
+                    blur,
+                    color: shadow.color,
+                }
+            } else {
+                let shape = Rect::new(rect.x + x, rect.y + y, rect.width, rect.height).inset(TRBL::all(-spread));
+                Command::DrawShadow {
+                    shape,
+                    radii: grow_radii(self.radii, spread),
+                    blur,
+                    color: shadow.color,
+                }
+            };
+            self.add_command(command);
         }
     }
 
+    /// Adds a command given in logical units. Geometry is converted to device
+    /// pixels and snapped to whole pixels.
     pub fn add_command(&mut self, command: Command) {
+        let scale_factor = self.scale_factor;
+        let command = match command {
+            Command::DrawRect(rect, color) => Command::DrawRect(rect.snap(scale_factor), color),
+            Command::DrawRoundedRect(rect, radii, color) => {
+                Command::DrawRoundedRect(rect.snap(scale_factor), radii.map(|radius| radius * scale_factor), color)
+            },
+            Command::DrawGradient(rect, radii, gradient) => {
+                let scale = |(x, y): (f32, f32)| (x * scale_factor, y * scale_factor);
+                let gradient = match gradient {
+                    Gradient::Linear { start, end, stops } => Gradient::Linear { start: scale(start), end: scale(end), stops },
+                    Gradient::Radial { center, radius, stops } => Gradient::Radial { center: scale(center), radius: radius * scale_factor, stops },
+                };
+                Command::DrawGradient(rect.snap(scale_factor), radii.map(|radius| radius * scale_factor), gradient)
+            },
+            Command::DrawImage { rect, radii, image, fit, scale } => Command::DrawImage {
+                rect: rect.snap(scale_factor),
+                radii: radii.map(|radius| radius * scale_factor),
+                image,
+                fit,
+                scale: scale * scale_factor,
+            },
+            Command::DrawText(run) => Command::DrawText(GlyphRun {
+                size: run.size * scale_factor,
+                origin: (run.origin.0 * scale_factor, run.origin.1 * scale_factor),
+                glyphs: run.glyphs.into_iter().map(|glyph| ShapedGlyph {
+                    x: glyph.x * scale_factor,
+                    advance: glyph.advance * scale_factor,
+                    ..glyph
+                }).collect(),
+                ..run
+            }),
+            Command::DrawRoundedBorder { rect, radii, widths, side, color, dash } => Command::DrawRoundedBorder {
+                rect: rect.snap(scale_factor),
+                radii: radii.map(|radius| radius * scale_factor),
+                widths: widths.map(|width| width * scale_factor),
+                side,
+                color,
+                dash: dash.map(|(dash, gap)| (dash * scale_factor, gap * scale_factor)),
+            },
+            Command::DrawShadow { shape, radii, blur, color } => Command::DrawShadow {
+                shape: shape.snap(scale_factor),
+                radii: radii.map(|radius| radius * scale_factor),
+                blur: blur * scale_factor,
+                color,
+            },
+            Command::DrawInsetShadow { rect, radii, shape, shape_radii, blur, color } => Command::DrawInsetShadow {
+                rect: rect.snap(scale_factor),
+                radii: radii.map(|radius| radius * scale_factor),
+                shape: shape.snap(scale_factor),
+                shape_radii: shape_radii.map(|radius| radius * scale_factor),
+                blur: blur * scale_factor,
+                color,
+            },
+            Command::PushClip(rect) => Command::PushClip(rect.snap(scale_factor)),
+            Command::PushTranslation(x, y) => Command::PushTranslation((x * scale_factor).round(), (y * scale_factor).round()),
+            command => command,
+        };
         self.commands.push(command);
     }
 
@@ -63,14 +566,88 @@ impl RenderContext {
     }
 }
 
+/// Places the gradient line of `gradient` on `rect` so that the corners get
+/// the first and last color.
+fn linear_gradient(rect: Rect, gradient: &LinearGradient) -> Gradient {
+    let angle = gradient.angle.to_radians();
+    let (x, y) = (angle.sin(), -angle.cos());
+    let length = (rect.width * x).abs() + (rect.height * y).abs();
+    let center = (rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+    Gradient::Linear {
+        start: (center.0 - x * length / 2.0, center.1 - y * length / 2.0),
+        end: (center.0 + x * length / 2.0, center.1 + y * length / 2.0),
+        stops: resolve_stops(&gradient.stops),
+    }
+}
+
+fn radial_gradient(rect: Rect, gradient: &RadialGradient, units: &Units) -> Gradient {
+    let center = (rect.x + rect.width * gradient.center.0, rect.y + rect.height * gradient.center.1);
+    let corner_x = (center.0 - rect.x).abs().max((rect.x + rect.width - center.0).abs());
+    let corner_y = (center.1 - rect.y).abs().max((rect.y + rect.height - center.1).abs());
+    let farthest = (corner_x * corner_x + corner_y * corner_y).sqrt();
+    Gradient::Radial {
+        center,
+        radius: gradient.radius.resolve(farthest, units).unwrap_or(farthest),
+        stops: resolve_stops(&gradient.stops),
+    }
+}
+
+/// Radii grown by `by`, corners without a radius stay sharp.
+fn grow_radii(radii: Corners<f32>, by: f32) -> Corners<f32> {
+    radii.map(|radius| if radius > 0.0 { (radius + by).max(0.0) } else { 0.0 })
+}
+
+/// Radii of the padding box, given the radii of the border box.
+fn inner_radii(radii: Corners<f32>, border: TRBL<f32>) -> Corners<f32> {
+    Corners::new(
+        (radii.top_left - border.top.max(border.left)).max(0.0),
+        (radii.top_right - border.top.max(border.right)).max(0.0),
+        (radii.bottom_right - border.bottom.max(border.right)).max(0.0),
+        (radii.bottom_left - border.bottom.max(border.left)).max(0.0),
+    )
+}
+
+/// Number of times `Surface::layout` lays out the tree before it leaves
+/// changes made by `LayoutElement::update` for the next call.
+pub const MAX_LAYOUT_PASSES: usize = 4;
+
+enum PaintStep {
+    /// Paints an element, carrying the origin of the content box of its
+    /// parent and of the padding box of its nearest positioned ancestor.
+    Enter(usize, (f32, f32), (f32, f32)),
+    /// Finishes an element after all of its children were painted.
+    Leave(usize, Rect),
+}
+
+struct LayoutFrame {
+    index: usize,
+    constraint: Constraint,
+    step: usize,
+    children: Vec<ElementId>,
+}
+
+/// Resolves offsets with percentages of the left and right offset taken of
+/// `width` and of the top and bottom offset taken of `height`.
+fn resolve_offsets(offsets: &TRBL<Option<Length>>, width: f32, height: f32, units: &Units) -> TRBL<Option<f32>> {
+    let resolve = |length: Option<Length>, base: f32| length.and_then(|length| length.resolve(base, units));
+    TRBL::new(resolve(offsets.top, height), resolve(offsets.right, width), resolve(offsets.bottom, height), resolve(offsets.left, width))
+}
+
+fn shift(position: f32, forward: Option<f32>, backward: Option<f32>) -> f32 {
+    match (forward, backward) {
+        (Some(forward), _) => position + forward,
+        (None, Some(backward)) => position - backward,
+        (None, None) => position,
+    }
+}
+
 #[allow(unused)]
 pub struct Surface {
     size: Size,
-    root_index: Option<usize>,
-    elements: Vec<Box<LayoutElement>>,
-    children: Vec<Vec<usize>>,
-    parents: Vec<Option<usize>>,
-    styles: Vec<Style>,
+    root: Option<ElementId>,
+    parents: Vec<Option<ElementId>>,
+    dirty: Vec<bool>,
+    constraints: Vec<Option<Constraint>>,
     layout_context: LayoutContext,
     render_context: RenderContext,
     free_list: Vec<usize>,
@@ -81,11 +658,10 @@ impl Surface {
     pub fn new(size: Size) -> Surface {
         Surface {
             size,
-            root_index: None,
-            elements: vec![],
-            children: vec![],
+            root: None,
             parents: vec![],
-            styles: vec![],
+            dirty: vec![],
+            constraints: vec![],
             layout_context: LayoutContext::new(),
             render_context: RenderContext::new(),
             free_list: vec![],
@@ -98,119 +674,1166 @@ impl Surface {
 
     pub fn resize(&mut self, size: Size) {
         self.size = size;
+        if let Some(root) = self.root {
+            self.mark_dirty(root.index());
+        }
+    }
+
+    /// Device pixels per logical unit. Layout happens in logical units,
+    /// painted commands are in device pixels.
+    pub fn get_scale_factor(&self) -> f32 {
+        self.layout_context.scale_factor
+    }
+
+    /// Changes the scale factor. As lengths in `Length::Px` depend on it,
+    /// the whole tree is laid out again.
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        if self.layout_context.scale_factor != scale_factor {
+            self.layout_context.scale_factor = scale_factor;
+            self.mark_all_dirty();
+        }
+    }
+
+    pub fn get_fonts(&self) -> &Fonts {
+        &self.layout_context.fonts
+    }
+
+    /// Fonts to load fonts into. As text may be measured with them, the
+    /// whole tree is laid out again.
+    pub fn get_fonts_mut(&mut self) -> &mut Fonts {
+        self.mark_all_dirty();
+        &mut self.layout_context.fonts
+    }
+
+    /// Font size of the element in logical units, as of the last layout.
+    pub fn get_font_size(&self, id: ElementId) -> Result<f32, SurfaceError> {
+        let index = self.layout_context.check(id)?;
+        Ok(self.layout_context.font_sizes[index])
     }
 
-    pub fn set_style(&mut self, index: usize, style: Style) {
-        self.styles[index] = style;
+    pub fn contains(&self, id: ElementId) -> bool {
+        self.layout_context.check(id).is_ok()
+    }
+
+    pub fn set_style(&mut self, id: ElementId, style: Style) -> Result<(), SurfaceError> {
+        let index = self.layout_context.check(id)?;
+        self.layout_context.styles[index] = style;
+        self.mark_dirty(index);
+        Ok(())
+    }
+
+    /// Forces `id` and its ancestors to be laid out again on the next call
+    /// to `layout`, e.g. after state inside the element changed.
+    pub fn invalidate(&mut self, id: ElementId) -> Result<(), SurfaceError> {
+        let index = self.layout_context.check(id)?;
+        self.mark_dirty(index);
+        Ok(())
+    }
+
+    pub fn get_element<T: LayoutElement + 'static>(&self, id: ElementId) -> Result<&T, SurfaceError> {
+        let index = self.layout_context.check(id)?;
+        let element: &LayoutElement = self.layout_context.elements[index].as_ref().ok_or(SurfaceError::Busy(id))?.as_ref();
+        element.as_any().downcast_ref::<T>().ok_or(SurfaceError::WrongType(id))
+    }
+
+    /// Mutable access to the element behind `id`. The element is marked
+    /// dirty, as the caller may change anything that affects its layout.
+    pub fn get_element_mut<T: LayoutElement + 'static>(&mut self, id: ElementId) -> Result<&mut T, SurfaceError> {
+        let index = self.layout_context.check(id)?;
+        self.mark_dirty(index);
+        let element: &mut LayoutElement = self.layout_context.elements[index].as_mut().ok_or(SurfaceError::Busy(id))?.as_mut();
+        element.as_any_mut().downcast_mut::<T>().ok_or(SurfaceError::WrongType(id))
+    }
+
+    pub fn needs_layout(&self) -> bool {
+        match self.root {
+            Some(root) => self.dirty[root.index()],
+            None => false,
+        }
     }
 
     pub fn paint(&mut self) -> Vec<Command> {
-        if let Some(i) = self.root_index {
-            let mut index_stack: Vec<usize> = vec![];
-            index_stack.push(i);
+        self.render_context.scale_factor = self.layout_context.scale_factor;
+        if let Some(root) = self.root {
+            let mut step_stack: Vec<PaintStep> = vec![];
+            step_stack.push(PaintStep::Enter(root.index(), (0.0, 0.0), (0.0, 0.0)));
 
-            while index_stack.len() > 0 {
-                let index = index_stack.pop().unwrap();
-                let rect = self.layout_context.get_rect(index);
+            while let Some(step) = step_stack.pop() {
+                let (index, parent_origin, positioned_origin) = match step {
+                    PaintStep::Enter(index, parent_origin, positioned_origin) => (index, parent_origin, positioned_origin),
+                    PaintStep::Leave(index, rect) => {
+                        self.prepare_render_context(index, rect);
+                        let style = &self.layout_context.styles[index];
+                        let node = self.layout_context.elements[index].as_mut().unwrap();
+                        node.paint_after(rect, &mut self.render_context, &style);
+                        if style.opacity < 1.0 {
+                            self.render_context.add_command(Command::PopOpacity);
+                        }
+                        continue;
+                    },
+                };
+                let style = &self.layout_context.styles[index];
+                let margin = self.layout_context.margins[index];
+                let border = self.layout_context.borders[index];
+                let padding = self.layout_context.paddings[index];
 
-                let mut node = &mut self.elements[index];
-                let style = &self.styles[index];
+                let (origin_x, origin_y) = match style.position {
+                    Position::Absolute => positioned_origin,
+                    Position::Static | Position::Relative => parent_origin,
+                };
+                let outer = self.layout_context.get_rect(index);
+                let mut rect = Rect::new(
+                    origin_x + outer.x + margin.left,
+                    origin_y + outer.y + margin.top,
+                    (outer.width - margin.horizontal()).max(0.0),
+                    (outer.height - margin.vertical()).max(0.0),
+                );
+
+                if let Position::Relative = style.position {
+                    let offsets = self.layout_context.offsets[index];
+                    rect.x = shift(rect.x, offsets.left, offsets.right);
+                    rect.y = shift(rect.y, offsets.top, offsets.bottom);
+                }
+
+                if style.opacity < 1.0 {
+                    self.render_context.add_command(Command::PushOpacity(style.opacity.max(0.0)));
+                }
+                self.prepare_render_context(index, rect);
+                let style = &self.layout_context.styles[index];
+                let mut node = self.layout_context.elements[index].as_mut().unwrap();
                 node.paint(rect, &mut self.render_context, &style);
 
-                index_stack.extend(self.children[index].iter());
+                let content_origin = (rect.x + border.left + padding.left, rect.y + border.top + padding.top);
+                let positioned_origin = match style.position {
+                    Position::Static if index != root.index() => positioned_origin,
+                    _ => (rect.x + border.left, rect.y + border.top),
+                };
+                step_stack.push(PaintStep::Leave(index, rect));
+                step_stack.extend(self.layout_context.children[index].iter().rev().map(|child| PaintStep::Enter(child.index(), content_origin, positioned_origin)));
             }
         }
 
         self.render_context.get_commands()
     }
 
-    pub fn layout(&mut self) {
-        if let Some(i) = self.root_index {
-            let mut index_stack: Vec<usize> = vec![];
-            let mut constraint_stack: Vec<Constraint> = vec![];
-            let mut result_stack: Vec<Size> = vec![];
+    /// Hands what the element at `index` with the border box `rect` needs
+    /// while painting to the render context.
+    fn prepare_render_context(&mut self, index: usize, rect: Rect) {
+        let units = self.layout_context.units(index);
+        let radii = self.layout_context.styles[index].border_radius
+            .resolve(rect.width.min(rect.height), &units)
+            .fit(rect.width, rect.height);
+        let direction = self.layout_context.directions[index];
 
-            index_stack.push(i);
-            self.layout_context.set_size(i, self.size.width, self.size.height);
-            self.layout_context.position_child(i, 0, 0);
-            constraint_stack.push(Constraint::fixed(self.size.width, self.size.height));
+        self.render_context.border = self.layout_context.borders[index];
+        self.render_context.padding = self.layout_context.paddings[index];
+        self.render_context.radii = match direction {
+            Direction::Ltr => radii,
+            Direction::Rtl => radii.mirror(),
+        };
+        self.render_context.units = units;
+        self.render_context.direction = direction;
+    }
 
-            while index_stack.len() > 0 {
-                let mut index = index_stack.pop().unwrap();
-                let mut constraint = constraint_stack.pop().unwrap();
+    /// Lays out the tree below the root element. Subtrees that are not dirty
+    /// and receive the same constraint as in the previous pass keep their
+    /// cached size and are skipped.
+    ///
+    /// Elements are laid out with the constraint of their parent narrowed by
+    /// their size properties and deflated by their margin, border and
+    /// padding, and report their size including all three. Child positions
+    /// are relative to the padding edge of the parent. Percentages are taken of the maximum of
+    /// the constraint of the parent, font sizes and directions are inherited
+    /// from the parent before layout starts.
+    ///
+    /// Absolutely positioned elements are left out of the children of their
+    /// parent. They are laid out afterwards within the padding box of their
+    /// nearest positioned ancestor, or the root, and placed by their offsets.
+    ///
+    /// Elements that change the tree in `LayoutElement::update` cause another
+    /// pass, up to `MAX_LAYOUT_PASSES` in one call.
+    pub fn layout(&mut self) -> Result<(), SurfaceError> {
+        for _ in 0..MAX_LAYOUT_PASSES {
+            let root = match self.root {
+                Some(root) => root,
+                None => return Ok(()),
+            };
+            let i = root.index();
+            let root_constraint = Constraint::fixed(self.size.width, self.size.height);
+            self.resolve_inherited(root)?;
+            if !self.needs_relayout(i, root_constraint) {
+                return Ok(());
+            }
 
-                let mut node = &mut self.elements[index];
-                let mut children = &mut self.children[index];
+            self.layout_subtree(i, root_constraint)?;
+            self.layout_context.set_position(i, 0.0, 0.0);
 
-                let mut size = result_stack.pop();
+            let absolutes: Vec<ElementId> = self.descendants(root)?
+                .filter(|id| self.layout_context.styles[id.index()].position == Position::Absolute)
+                .collect();
+            for id in absolutes {
+                self.layout_absolute(id.index())?;
+            }
 
-                match node.layout(constraint, &mut self.layout_context, children, size) {
-                    LayoutResult::LayoutChild(child_index, child_constraint) => {
+            self.update(root)?;
+        }
+        Ok(())
+    }
 
-                        index_stack.push(index);
-                        constraint_stack.push(constraint);
+    /// Inherits font sizes and directions down the tree. Elements where
+    /// either changed are marked dirty, as their lengths in `Length::Em` or
+    /// their placement changed as well.
+    fn resolve_inherited(&mut self, root: ElementId) -> Result<(), SurfaceError> {
+        let ids: Vec<ElementId> = Some(root).into_iter().chain(self.descendants(root)?).collect();
+        for id in ids {
+            let index = id.index();
+            let (inherited_font_size, inherited_direction) = match self.parents[index] {
+                Some(parent) if id != root => (self.layout_context.font_sizes[parent.index()], self.layout_context.directions[parent.index()]),
+                _ => (DEFAULT_FONT_SIZE, Direction::Ltr),
+            };
+            let style = &self.layout_context.styles[index];
+            let units = Units::new(self.layout_context.scale_factor, inherited_font_size);
+            let font_size = match style.font_size {
+                Some(font_size) => font_size.resolve(inherited_font_size, &units).unwrap_or(inherited_font_size),
+                None => inherited_font_size,
+            };
+            let direction = style.direction.unwrap_or(inherited_direction);
 
-                        index_stack.push(child_index);
-                        constraint_stack.push(child_constraint);
-                    },
-                    LayoutResult::Done(result_size) => {
-                        self.layout_context.set_size(index, result_size.width, result_size.height);
-                        result_stack.push(result_size);
-                    },
-                }
+            if self.layout_context.font_sizes[index] != font_size || self.layout_context.directions[index] != direction {
+                self.layout_context.font_sizes[index] = font_size;
+                self.layout_context.directions[index] = direction;
+                self.mark_dirty(index);
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `LayoutElement::update` on every element of the tree. The
+    /// element is taken out of the surface for the duration of the call,
+    /// `get_element` returns `SurfaceError::Busy` for it meanwhile.
+    fn update(&mut self, root: ElementId) -> Result<(), SurfaceError> {
+        let ids: Vec<ElementId> = Some(root).into_iter().chain(self.descendants(root)?).collect();
+        for id in ids {
+            if !self.contains(id) {
+                continue;
+            }
+            let mut element = self.layout_context.elements[id.index()].take().unwrap();
+            let result = element.update(id, self);
+            if self.contains(id) {
+                self.layout_context.elements[id.index()] = Some(element);
+            } else {
+                // removed itself or an ancestor, `remove` could not reach it
+                element.teardown();
+            }
+            result?;
+        }
+        Ok(())
+    }
+
+    fn layout_subtree(&mut self, index: usize, constraint: Constraint) -> Result<(), SurfaceError> {
+        let mut frame_stack: Vec<LayoutFrame> = vec![];
+        let mut result_stack: Vec<Size> = vec![];
+
+        frame_stack.push(self.frame(index, constraint));
+
+        while frame_stack.len() > 0 {
+            let mut frame = frame_stack.pop().unwrap();
+            let index = frame.index;
+
+            let size = result_stack.pop();
+
+            let (base_width, base_height) = (frame.constraint.max_width, frame.constraint.max_height);
+            let (margin, border, padding) = self.layout_context.resolve_insets(index, base_width);
+            let inner = border + padding;
+            let insets_width = margin.horizontal() + inner.horizontal();
+            let insets_height = margin.vertical() + inner.vertical();
+            let box_constraint = frame.constraint.deflate(margin.horizontal(), margin.vertical());
+            let inner_constraint = self.layout_context.size_constraint(index, box_constraint, base_width, base_height)?
+                .deflate(inner.horizontal(), inner.vertical());
+
+            // the element is taken out so it can query its children through the context
+            let mut node = self.layout_context.elements[index].take().unwrap();
+            self.layout_context.laid_out_children = frame.step;
+            self.layout_context.direction = self.layout_context.directions[index];
+            self.layout_context.units.set(self.layout_context.units(index));
+            let result = node.layout(inner_constraint, &mut self.layout_context, &mut frame.children, size);
+            let baseline = node.baseline();
+            self.layout_context.elements[index] = Some(node);
+
+            match result? {
+                LayoutResult::LayoutChild(child, child_constraint) => {
+                    let child_index = self.layout_context.check(child)?;
+
+                    frame.step += 1;
+                    frame_stack.push(frame);
+
+                    if self.needs_relayout(child_index, child_constraint) {
+                        frame_stack.push(self.frame(child_index, child_constraint));
+                    } else {
+                        result_stack.push(self.layout_context.get_child_size(child)?);
+                    }
+                },
+                LayoutResult::Done(content_size) => {
+                    let result_size = Size::new(content_size.width + insets_width, content_size.height + insets_height);
+                    self.layout_context.set_size(index, result_size.width, result_size.height);
+                    self.layout_context.baselines[index] = baseline.map(|baseline| baseline + margin.top + inner.top);
+                    let units = self.layout_context.units(index);
+                    self.layout_context.offsets[index] = resolve_offsets(&self.layout_context.styles[index].offsets, base_width, base_height, &units);
+                    self.layout_context.margins[index] = margin;
+                    self.layout_context.borders[index] = border;
+                    self.layout_context.paddings[index] = padding;
+                    self.constraints[index] = Some(frame.constraint);
+                    self.dirty[index] = false;
+                    // the element may have learned about its content, e.g. the
+                    // real heights of list rows
+                    self.forget_intrinsics(index);
+                    result_stack.push(result_size);
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn frame(&self, index: usize, constraint: Constraint) -> LayoutFrame {
+        LayoutFrame {
+            index,
+            constraint,
+            step: 0,
+            children: self.layout_context.flow_children(index),
+        }
+    }
+
+    fn layout_absolute(&mut self, index: usize) -> Result<(), SurfaceError> {
+        let mut containing_block = self.parents[index].unwrap().index();
+        while let Some(parent) = self.parents[containing_block] {
+            if self.layout_context.styles[containing_block].position != Position::Static {
+                break;
             }
+            containing_block = parent.index();
         }
+
+        let outer = self.layout_context.get_rect(containing_block);
+        let insets = self.layout_context.margins[containing_block] + self.layout_context.borders[containing_block];
+        let width = (outer.width - insets.horizontal()).max(0.0);
+        let height = (outer.height - insets.vertical()).max(0.0);
+
+        let units = self.layout_context.units(index);
+        let offsets = resolve_offsets(&self.layout_context.styles[index].offsets, width, height, &units);
+        let available_width = (width - offsets.left.unwrap_or(0.0) - offsets.right.unwrap_or(0.0)).max(0.0);
+        let available_height = (height - offsets.top.unwrap_or(0.0) - offsets.bottom.unwrap_or(0.0)).max(0.0);
+        let min_width = if offsets.left.is_some() && offsets.right.is_some() { available_width } else { 0.0 };
+        let min_height = if offsets.top.is_some() && offsets.bottom.is_some() { available_height } else { 0.0 };
+        let constraint = Constraint::new(min_width, available_width, min_height, available_height);
+
+        if self.needs_relayout(index, constraint) {
+            self.layout_subtree(index, constraint)?;
+        }
+
+        let size = self.layout_context.get_rect(index);
+        let x = match (offsets.left, offsets.right) {
+            (Some(left), _) => left,
+            (None, Some(right)) => (width - right - size.width).max(0.0),
+            (None, None) => 0.0,
+        };
+        let y = match (offsets.top, offsets.bottom) {
+            (Some(top), _) => top,
+            (None, Some(bottom)) => (height - bottom - size.height).max(0.0),
+            (None, None) => 0.0,
+        };
+        self.layout_context.set_position(index, x, y);
+        Ok(())
+    }
+
+    fn needs_relayout(&self, index: usize, constraint: Constraint) -> bool {
+        self.dirty[index] || self.constraints[index] != Some(constraint)
+    }
+
+    fn mark_dirty(&mut self, index: usize) {
+        let mut current = Some(index);
+        while let Some(index) = current {
+            self.dirty[index] = true;
+            current = self.parents[index].map(|parent| parent.index());
+        }
+        self.forget_intrinsics(index);
     }
 
-    pub fn set_root(&mut self, index: usize) {
-        self.root_index = Some(index);
+    /// Drops the intrinsic sizes remembered for `index` and its ancestors, as
+    /// they depend on it.
+    fn forget_intrinsics(&self, index: usize) {
+        let mut current = Some(index);
+        while let Some(index) = current {
+            self.layout_context.forget_intrinsics(index);
+            current = self.parents[index].map(|parent| parent.index());
+        }
     }
 
-    
-    pub fn insert(&mut self, element: Box<LayoutElement>) -> usize {
+    fn mark_all_dirty(&mut self) {
+        for index in 0..self.dirty.len() {
+            self.dirty[index] = true;
+            self.layout_context.forget_intrinsics(index);
+        }
+    }
+
+    pub fn set_root(&mut self, id: ElementId) -> Result<(), SurfaceError> {
+        let index = self.layout_context.check(id)?;
+        self.root = Some(id);
+        self.mark_dirty(index);
+        Ok(())
+    }
+
+    pub fn insert(&mut self, element: Box<LayoutElement>) -> ElementId {
         if let Some(index) = self.free_list.pop() {
-            self.elements[index] = element;
-            self.styles[index] = Style::empty();
-            self.layout_context.set_size(index, 0, 0);
-            self.layout_context.position_child(index, 0, 0);
-            return index;
+            self.reset(index);
+            self.layout_context.elements[index] = Some(element);
+            return self.layout_context.reuse(index);
         }
 
-        let index = self.elements.len();
-        self.layout_context.insert(Rect::new(0, 0, 0, 0));
-        self.elements.push(element);
-        self.children.push(vec![]);
+        self.layout_context.elements.push(Some(element));
+        self.layout_context.children.push(vec![]);
         self.parents.push(None);
-        self.styles.push(Style::empty());
-        index
+        self.dirty.push(true);
+        self.constraints.push(None);
+        self.layout_context.insert(Rect::new(0.0, 0.0, 0.0, 0.0))
     }
 
-    pub fn remove(&mut self, index: usize) {
+    pub fn remove(&mut self, id: ElementId) -> Result<(), SurfaceError> {
+        self.layout_context.check(id)?;
+
         let mut stack = vec![];
-        stack.push(index);
+        stack.push(id);
 
         while stack.len() > 0 {
-            let index = stack.pop().unwrap();
+            let id = stack.pop().unwrap();
+            let index = id.index();
 
-            if let Some(root_index) = self.root_index {
-                if root_index == index {
-                    self.root_index = None;
-                }
+            if self.root == Some(id) {
+                self.root = None;
             }
 
-            if let Some(parent_index) = self.parents[index] {
-                self.children[parent_index].remove_item(&index);
-            }
+            self.unlink(id);
+
+            stack.extend(self.layout_context.children[index].iter());
 
-            stack.extend(self.children[index].iter());
-            self.children[index].clear();
+            if let Some(mut element) = self.layout_context.elements[index].take() {
+                element.teardown();
+            }
+            self.reset(index);
 
+            self.layout_context.kill(index);
             self.free_list.push(index);
         }
+        Ok(())
+    }
+
+    fn reset(&mut self, index: usize) {
+        self.layout_context.children[index].clear();
+        self.parents[index] = None;
+        self.dirty[index] = true;
+        self.constraints[index] = None;
+        self.layout_context.reset(index);
+    }
+
+    /// Appends `child` to the children of `parent`, removing it from its
+    /// previous parent first.
+    pub fn set_parent(&mut self, child: ElementId, parent: ElementId) -> Result<(), SurfaceError> {
+        let parent_index = self.layout_context.check(parent)?;
+        self.check_reparent(child, parent)?;
+
+        self.unlink(child);
+        self.link(child, parent, self.layout_context.children[parent_index].len());
+        Ok(())
+    }
+
+    /// Inserts `child` into the children of `parent` at `position`. The
+    /// position refers to the children list after `child` was removed from
+    /// its previous parent.
+    pub fn insert_child_at(&mut self, parent: ElementId, position: usize, child: ElementId) -> Result<(), SurfaceError> {
+        let parent_index = self.layout_context.check(parent)?;
+        self.check_reparent(child, parent)?;
+
+        let len = self.layout_context.children[parent_index].iter().filter(|c| **c != child).count();
+        if position > len {
+            return Err(SurfaceError::OutOfBounds(parent, position));
+        }
+
+        self.unlink(child);
+        self.link(child, parent, position);
+        Ok(())
+    }
+
+    /// Inserts `child` right before `sibling` in the children of the
+    /// parent of `sibling`.
+    pub fn insert_before(&mut self, sibling: ElementId, child: ElementId) -> Result<(), SurfaceError> {
+        let sibling_index = self.layout_context.check(sibling)?;
+        let parent = self.parents[sibling_index].ok_or(SurfaceError::Detached(sibling))?;
+        if sibling == child {
+            return Ok(());
+        }
+        self.check_reparent(child, parent)?;
+
+        self.unlink(child);
+        let position = self.position_in_parent(sibling).unwrap();
+        self.link(child, parent, position);
+        Ok(())
+    }
+
+    /// Removes `child` from its parent. The element and its subtree stay
+    /// alive and can be attached somewhere else later.
+    pub fn detach(&mut self, child: ElementId) -> Result<(), SurfaceError> {
+        self.layout_context.check(child)?;
+        self.unlink(child);
+        Ok(())
+    }
+
+    /// Moves the child of `parent` at position `from` to position `to`.
+    pub fn move_child(&mut self, parent: ElementId, from: usize, to: usize) -> Result<(), SurfaceError> {
+        let parent_index = self.layout_context.check(parent)?;
+        let children = &mut self.layout_context.children[parent_index];
+        if from >= children.len() {
+            return Err(SurfaceError::OutOfBounds(parent, from));
+        }
+        if to >= children.len() {
+            return Err(SurfaceError::OutOfBounds(parent, to));
+        }
+
+        let child = children.remove(from);
+        children.insert(to, child);
+        self.mark_dirty(parent_index);
+        Ok(())
+    }
+
+    /// Swaps the children of `parent` at positions `a` and `b`.
+    pub fn swap_children(&mut self, parent: ElementId, a: usize, b: usize) -> Result<(), SurfaceError> {
+        let parent_index = self.layout_context.check(parent)?;
+        let children = &mut self.layout_context.children[parent_index];
+        if a >= children.len() {
+            return Err(SurfaceError::OutOfBounds(parent, a));
+        }
+        if b >= children.len() {
+            return Err(SurfaceError::OutOfBounds(parent, b));
+        }
+
+        children.swap(a, b);
+        self.mark_dirty(parent_index);
+        Ok(())
+    }
+
+    pub fn get_parent(&self, id: ElementId) -> Result<Option<ElementId>, SurfaceError> {
+        let index = self.layout_context.check(id)?;
+        Ok(self.parents[index])
+    }
+
+    pub fn children(&self, id: ElementId) -> Result<Children, SurfaceError> {
+        let index = self.layout_context.check(id)?;
+        Ok(self.layout_context.children[index].iter().cloned())
+    }
+
+    /// Iterates over the parent, grand parent and so on up to the root of
+    /// the tree `id` is part of.
+    pub fn ancestors(&self, id: ElementId) -> Result<Ancestors, SurfaceError> {
+        let index = self.layout_context.check(id)?;
+        Ok(Ancestors {
+            parents: &self.parents,
+            next: self.parents[index],
+        })
+    }
+
+    /// Iterates over all elements below `id` in depth first order.
+    pub fn descendants(&self, id: ElementId) -> Result<Descendants, SurfaceError> {
+        let index = self.layout_context.check(id)?;
+        Ok(Descendants {
+            children: &self.layout_context.children,
+            stack: self.layout_context.children[index].iter().rev().cloned().collect(),
+        })
+    }
+
+    fn check_reparent(&self, child: ElementId, parent: ElementId) -> Result<(), SurfaceError> {
+        self.layout_context.check(child)?;
+        if child == parent || self.ancestors(parent)?.any(|ancestor| ancestor == child) {
+            return Err(SurfaceError::Cycle(child, parent));
+        }
+        Ok(())
+    }
+
+    fn position_in_parent(&self, child: ElementId) -> Option<usize> {
+        self.parents[child.index()]
+            .and_then(|parent| self.layout_context.children[parent.index()].iter().position(|c| *c == child))
+    }
+
+    fn link(&mut self, child: ElementId, parent: ElementId, position: usize) {
+        self.layout_context.children[parent.index()].insert(position, child);
+        self.parents[child.index()] = Some(parent);
+        self.mark_dirty(parent.index());
+    }
+
+    fn unlink(&mut self, child: ElementId) {
+        if let Some(position) = self.position_in_parent(child) {
+            let parent = self.parents[child.index()].unwrap();
+            self.layout_context.children[parent.index()].remove(position);
+            self.mark_dirty(parent.index());
+        }
+        self.parents[child.index()] = None;
+    }
+}
+
+pub type Children<'a> = Cloned<Iter<'a, ElementId>>;
+
+pub struct Ancestors<'a> {
+    parents: &'a Vec<Option<ElementId>>,
+    next: Option<ElementId>,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = ElementId;
+
+    fn next(&mut self) -> Option<ElementId> {
+        let current = self.next;
+        if let Some(id) = current {
+            self.next = self.parents[id.index()];
+        }
+        current
+    }
+}
+
+pub struct Descendants<'a> {
+    children: &'a Vec<Vec<ElementId>>,
+    stack: Vec<ElementId>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = ElementId;
+
+    fn next(&mut self) -> Option<ElementId> {
+        let current = self.stack.pop();
+        if let Some(id) = current {
+            self.stack.extend(self.children[id.index()].iter().rev());
+        }
+        current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::color::*;
+    use ::elements::*;
+    use ::std::rc::Rc;
+
+    /// Element 10 high and half as wide as it may be that counts the calls
+    /// the surface makes to it.
+    struct Probe {
+        layouts: Rc<Cell<usize>>,
+        teardowns: Rc<Cell<usize>>,
+    }
+
+    impl Probe {
+        fn new() -> Probe {
+            Probe {
+                layouts: Rc::new(Cell::new(0)),
+                teardowns: Rc::new(Cell::new(0)),
+            }
+        }
+    }
+
+    impl LayoutElement for Probe {
+        #[allow(unused)]
+        fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<ElementId>, requested: Option<Size>) -> Result<LayoutResult, SurfaceError> {
+            self.layouts.set(self.layouts.get() + 1);
+            Ok(LayoutResult::Done(Size::new(constraint.max_width / 2.0, 10.0)))
+        }
+
+        fn teardown(&mut self) {
+            self.teardowns.set(self.teardowns.get() + 1);
+        }
+    }
+
+    fn new_surface() -> Surface {
+        Surface::new(Size::new(200.0, 100.0))
+    }
+
+    #[test]
+    fn removed_slots_are_reused_with_a_new_generation() {
+        let mut surface = new_surface();
+        let removed = surface.insert(Box::new(BoxElement::new()));
+        surface.remove(removed).unwrap();
+        let id = surface.insert(Box::new(BoxElement::new()));
+
+        assert_eq!(id.index(), removed.index());
+        assert!(id.generation() != removed.generation());
+        assert!(surface.contains(id));
+        assert!(!surface.contains(removed));
+    }
+
+    #[test]
+    fn dead_ids_are_rejected() {
+        let mut surface = new_surface();
+        let removed = surface.insert(Box::new(BoxElement::new()));
+        surface.remove(removed).unwrap();
+        let id = surface.insert(Box::new(BoxElement::new()));
+
+        assert_eq!(surface.set_style(removed, Style::empty()), Err(SurfaceError::DeadElement(removed)));
+        assert_eq!(surface.set_root(removed), Err(SurfaceError::DeadElement(removed)));
+        assert_eq!(surface.set_parent(removed, id), Err(SurfaceError::DeadElement(removed)));
+        assert_eq!(surface.remove(removed), Err(SurfaceError::DeadElement(removed)));
+        assert!(surface.get_element::<BoxElement>(removed).is_err());
+
+        surface.set_root(id).unwrap();
+        surface.layout().unwrap();
+    }
+
+    fn children(surface: &Surface, id: ElementId) -> Vec<ElementId> {
+        surface.children(id).unwrap().collect()
+    }
+
+    #[test]
+    fn set_parent_moves_and_appends() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        let a = surface.insert(Box::new(BoxElement::new()));
+        let b = surface.insert(Box::new(BoxElement::new()));
+
+        surface.set_parent(a, root).unwrap();
+        surface.set_parent(b, root).unwrap();
+        surface.set_parent(a, root).unwrap();
+        assert_eq!(children(&surface, root), vec![b, a]);
+
+        surface.set_parent(a, b).unwrap();
+        assert_eq!(children(&surface, root), vec![b]);
+        assert_eq!(surface.get_parent(a).unwrap(), Some(b));
     }
 
-    pub fn set_parent(&mut self, child_index: usize, parent_index: usize) {
-        self.children[parent_index].push(child_index);
-        self.parents[child_index] = Some(parent_index);
+    #[test]
+    fn children_are_inserted_moved_and_swapped() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        let a = surface.insert(Box::new(BoxElement::new()));
+        let b = surface.insert(Box::new(BoxElement::new()));
+        let c = surface.insert(Box::new(BoxElement::new()));
+        surface.set_parent(a, root).unwrap();
+        surface.set_parent(b, root).unwrap();
+
+        surface.insert_before(a, c).unwrap();
+        assert_eq!(children(&surface, root), vec![c, a, b]);
+        surface.insert_child_at(root, 2, c).unwrap();
+        assert_eq!(children(&surface, root), vec![a, b, c]);
+        surface.move_child(root, 0, 2).unwrap();
+        assert_eq!(children(&surface, root), vec![b, c, a]);
+        surface.swap_children(root, 0, 1).unwrap();
+        assert_eq!(children(&surface, root), vec![c, b, a]);
+
+        assert_eq!(surface.insert_child_at(root, 4, c), Err(SurfaceError::OutOfBounds(root, 4)));
+        assert_eq!(surface.move_child(root, 3, 0), Err(SurfaceError::OutOfBounds(root, 3)));
+        assert_eq!(surface.swap_children(root, 0, 3), Err(SurfaceError::OutOfBounds(root, 3)));
+        assert_eq!(surface.insert_before(root, a), Err(SurfaceError::Detached(root)));
+    }
+
+    #[test]
+    fn detached_subtrees_stay_alive() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        let a = surface.insert(Box::new(BoxElement::new()));
+        let b = surface.insert(Box::new(BoxElement::new()));
+        surface.set_parent(a, root).unwrap();
+        surface.set_parent(b, a).unwrap();
+
+        surface.detach(a).unwrap();
+        assert_eq!(children(&surface, root), vec![]);
+        assert_eq!(surface.get_parent(a).unwrap(), None);
+        assert_eq!(children(&surface, a), vec![b]);
+    }
+
+    #[test]
+    fn elements_can_not_become_their_own_descendants() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        let a = surface.insert(Box::new(BoxElement::new()));
+        let b = surface.insert(Box::new(BoxElement::new()));
+        surface.set_parent(a, root).unwrap();
+        surface.set_parent(b, a).unwrap();
+
+        assert_eq!(surface.set_parent(root, b), Err(SurfaceError::Cycle(root, b)));
+        assert_eq!(surface.insert_child_at(a, 0, a), Err(SurfaceError::Cycle(a, a)));
+        assert_eq!(surface.insert_before(b, root), Err(SurfaceError::Cycle(root, a)));
+        assert_eq!(surface.get_parent(root).unwrap(), None);
+    }
+
+    #[test]
+    fn ancestors_and_descendants() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        let a = surface.insert(Box::new(BoxElement::new()));
+        let b = surface.insert(Box::new(BoxElement::new()));
+        let c = surface.insert(Box::new(BoxElement::new()));
+        surface.set_parent(a, root).unwrap();
+        surface.set_parent(b, a).unwrap();
+        surface.set_parent(c, root).unwrap();
+
+        assert_eq!(surface.ancestors(b).unwrap().collect::<Vec<ElementId>>(), vec![a, root]);
+        assert_eq!(surface.descendants(root).unwrap().collect::<Vec<ElementId>>(), vec![a, b, c]);
+        assert_eq!(surface.descendants(b).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn remove_tears_down_the_subtree() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        let probe = Probe::new();
+        let teardowns = probe.teardowns.clone();
+        let a = surface.insert(Box::new(probe));
+        let b = surface.insert(Box::new(Probe { teardowns: teardowns.clone(), ..Probe::new() }));
+        surface.set_parent(a, root).unwrap();
+        surface.set_parent(b, a).unwrap();
+
+        surface.remove(a).unwrap();
+        assert_eq!(teardowns.get(), 2);
+        assert!(!surface.contains(a) && !surface.contains(b));
+        assert_eq!(children(&surface, root), vec![]);
+    }
+
+    #[test]
+    fn removing_the_root_leaves_nothing_to_lay_out() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        surface.set_root(root).unwrap();
+        surface.remove(root).unwrap();
+
+        assert!(!surface.needs_layout());
+        surface.layout().unwrap();
+        assert!(surface.paint().is_empty());
+    }
+
+    #[test]
+    fn reused_slots_start_out_empty() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        let a = surface.insert(Box::new(BoxElement::new()));
+        let b = surface.insert(Box::new(BoxElement::new()));
+        surface.set_parent(a, root).unwrap();
+        surface.set_parent(b, a).unwrap();
+        let mut style = Style::empty();
+        style.width = Length::Dp(50.0);
+        surface.set_style(a, style).unwrap();
+        surface.set_root(root).unwrap();
+        surface.layout().unwrap();
+
+        surface.remove(a).unwrap();
+        let c = surface.insert(Box::new(BoxElement::new()));
+        let d = surface.insert(Box::new(BoxElement::new()));
+        for id in vec![c, d] {
+            assert_eq!(surface.get_parent(id).unwrap(), None);
+            assert_eq!(children(&surface, id), vec![]);
+            assert_eq!(surface.layout_context.get_rect(id.index()), Rect::new(0.0, 0.0, 0.0, 0.0));
+            assert_eq!(surface.layout_context.styles[id.index()].width, Length::Auto);
+            assert!(surface.dirty[id.index()]);
+            assert_eq!(surface.constraints[id.index()], None);
+        }
+    }
+
+    #[test]
+    fn clean_trees_are_not_laid_out_again() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        let probe = Probe::new();
+        let layouts = probe.layouts.clone();
+        let a = surface.insert(Box::new(probe));
+        surface.set_parent(a, root).unwrap();
+        surface.set_root(root).unwrap();
+
+        surface.layout().unwrap();
+        assert_eq!(layouts.get(), 1);
+        assert!(!surface.needs_layout());
+        surface.layout().unwrap();
+        assert_eq!(layouts.get(), 1);
+    }
+
+    #[test]
+    fn only_dirty_elements_are_laid_out_again() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        let (a, b) = (Probe::new(), Probe::new());
+        let (a_layouts, b_layouts) = (a.layouts.clone(), b.layouts.clone());
+        let a = surface.insert(Box::new(a));
+        let b = surface.insert(Box::new(b));
+        surface.set_parent(a, root).unwrap();
+        surface.set_parent(b, root).unwrap();
+        surface.set_root(root).unwrap();
+        surface.layout().unwrap();
+
+        surface.set_style(a, Style::empty()).unwrap();
+        assert!(surface.needs_layout());
+        surface.layout().unwrap();
+        assert_eq!((a_layouts.get(), b_layouts.get()), (2, 1));
+
+        surface.invalidate(b).unwrap();
+        surface.layout().unwrap();
+        assert_eq!((a_layouts.get(), b_layouts.get()), (2, 2));
+    }
+
+    #[test]
+    fn changed_constraints_lay_out_clean_elements_again() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        let probe = Probe::new();
+        let layouts = probe.layouts.clone();
+        let a = surface.insert(Box::new(probe));
+        surface.set_parent(a, root).unwrap();
+        surface.set_root(root).unwrap();
+        surface.layout().unwrap();
+
+        surface.resize(Size::new(300.0, 100.0));
+        surface.layout().unwrap();
+        assert_eq!(layouts.get(), 2);
+        assert_eq!(surface.layout_context.get_rect(a.index()).width, 150.0);
+    }
+
+    fn filled() -> Style {
+        Style::new(Background::Color(Rgba::new(1.0, 0.0, 0.0, 1.0)), TRBL::all(Border::none()))
+    }
+
+    fn absolute(top: Option<f32>, right: Option<f32>, bottom: Option<f32>, left: Option<f32>) -> Style {
+        let mut style = filled();
+        style.position = Position::Absolute;
+        style.offsets = TRBL::new(top.map(Length::Dp), right.map(Length::Dp), bottom.map(Length::Dp), left.map(Length::Dp));
+        style
+    }
+
+    /// Filled rects after a layout, in the order they are painted.
+    fn painted_rects(surface: &mut Surface) -> Vec<Rect> {
+        surface.layout().unwrap();
+        surface.paint().into_iter().filter_map(|command| match command {
+            Command::DrawRect(rect, _) => Some(rect),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn absolute_elements_are_placed_by_their_offsets() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        surface.set_root(root).unwrap();
+        let corner = surface.insert(Box::new(FixedElement::new(30.0, 40.0)));
+        surface.set_style(corner, absolute(None, Some(10.0), Some(20.0), None)).unwrap();
+        surface.set_parent(corner, root).unwrap();
+        let bar = surface.insert(Box::new(FixedElement::new(5.0, 5.0)));
+        surface.set_style(bar, absolute(Some(90.0), Some(10.0), None, Some(10.0))).unwrap();
+        surface.set_parent(bar, root).unwrap();
+
+        assert_eq!(painted_rects(&mut surface), vec![Rect::new(160.0, 40.0, 30.0, 40.0), Rect::new(10.0, 90.0, 180.0, 5.0)]);
+    }
+
+    #[test]
+    fn absolute_elements_are_placed_in_their_nearest_positioned_ancestor() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(BoxElement::new()));
+        surface.set_root(root).unwrap();
+        let panel = surface.insert(Box::new(BoxElement::new()));
+        let mut style = filled();
+        style.position = Position::Relative;
+        style.margin = TRBL::all(Length::Dp(20.0));
+        style.padding = TRBL::all(Length::Dp(10.0));
+        style.width = Length::Dp(100.0);
+        style.height = Length::Dp(50.0);
+        surface.set_style(panel, style).unwrap();
+        surface.set_parent(panel, root).unwrap();
+        let content = surface.insert(Box::new(BoxElement::new()));
+        surface.set_parent(content, panel).unwrap();
+        let pin = surface.insert(Box::new(FixedElement::new(5.0, 5.0)));
+        surface.set_style(pin, absolute(Some(0.0), None, None, Some(0.0))).unwrap();
+        surface.set_parent(pin, content).unwrap();
+
+        assert_eq!(painted_rects(&mut surface), vec![Rect::new(20.0, 20.0, 100.0, 50.0), Rect::new(20.0, 20.0, 5.0, 5.0)]);
+    }
+
+    #[test]
+    fn absolute_elements_do_not_take_room_in_their_parent() {
+        let mut surface = new_surface();
+        let root = surface.insert(Box::new(FlexElement::new(Axis::Horizontal).with_align_items(Align::Start)));
+        surface.set_root(root).unwrap();
+        let overlay = surface.insert(Box::new(FixedElement::new(50.0, 10.0)));
+        surface.set_style(overlay, absolute(None, None, Some(0.0), None)).unwrap();
+        surface.set_parent(overlay, root).unwrap();
+        let item = surface.insert(Box::new(FixedElement::new(20.0, 10.0)));
+        surface.set_style(item, filled()).unwrap();
+        surface.set_parent(item, root).unwrap();
+
+        let rects = painted_rects(&mut surface);
+        assert!(rects.contains(&Rect::new(0.0, 0.0, 20.0, 10.0)));
+        assert!(rects.contains(&Rect::new(0.0, 90.0, 50.0, 10.0)));
+    }
+
+    /// Rows estimated 20 high that lay out 20 or 30 high, counting how
+    /// often they are built.
+    struct Rows {
+        built: Rc<Cell<usize>>,
+    }
+
+    impl ListDataSource for Rows {
+        fn row_count(&self) -> usize {
+            1000
+        }
+
+        #[allow(unused)]
+        fn row_height(&self, row: usize) -> f32 {
+            20.0
+        }
+
+        fn build_row(&mut self, row: usize, surface: &mut Surface) -> Result<ElementId, SurfaceError> {
+            self.built.set(self.built.get() + 1);
+            Ok(surface.insert(Box::new(FixedElement::new(10.0, if row % 2 == 0 { 20.0 } else { 30.0 }))))
+        }
+    }
+
+    fn visible_rows(surface: &Surface, list: ElementId) -> Vec<usize> {
+        surface.get_element::<ListElement>(list).unwrap().get_rows().iter().map(|&(row, _)| row).collect()
+    }
+
+    #[test]
+    fn lists_build_the_visible_rows_as_children() {
+        let mut surface = new_surface();
+        let built = Rc::new(Cell::new(0));
+        let list = surface.insert(Box::new(ListElement::new(Box::new(Rows { built: built.clone() }))));
+        surface.set_root(list).unwrap();
+
+        surface.layout().unwrap();
+        // rows are 20, 30, 20, 30 high once laid out, the 5th estimate fit as well
+        assert_eq!(visible_rows(&surface, list), vec![0, 1, 2, 3]);
+        assert_eq!(children(&surface, list).len(), 4);
+        assert_eq!(built.get(), 5);
+        assert!(!surface.needs_layout());
+    }
+
+    #[test]
+    fn scrolling_lists_replace_rows_that_left_the_viewport() {
+        let mut surface = new_surface();
+        let built = Rc::new(Cell::new(0));
+        let list = surface.insert(Box::new(ListElement::new(Box::new(Rows { built: built.clone() }))));
+        surface.set_root(list).unwrap();
+        surface.layout().unwrap();
+        let first = children(&surface, list)[0];
+
+        surface.get_element_mut::<ListElement>(list).unwrap().scroll_by(60.0);
+        surface.layout().unwrap();
+        assert_eq!(visible_rows(&surface, list), vec![2, 3, 4, 5, 6]);
+        assert_eq!(children(&surface, list).len(), 5);
+        assert!(!surface.contains(first));
+
+        surface.get_element_mut::<ListElement>(list).unwrap().scroll_to(::std::f32::MAX);
+        surface.layout().unwrap();
+        // rows that became visible turned out taller than estimated
+        assert!(visible_rows(&surface, list)[0] > 990);
+        assert!(children(&surface, list).len() <= 6);
+    }
+
+    #[test]
+    fn reloaded_lists_build_their_rows_again() {
+        let mut surface = new_surface();
+        let built = Rc::new(Cell::new(0));
+        let list = surface.insert(Box::new(ListElement::new(Box::new(Rows { built: built.clone() }))));
+        surface.set_root(list).unwrap();
+        surface.layout().unwrap();
+        let rows = children(&surface, list);
+        built.set(0);
+
+        surface.get_element_mut::<ListElement>(list).unwrap().reload();
+        surface.layout().unwrap();
+        assert_eq!(built.get(), 5);
+        assert!(rows.iter().all(|row| !surface.contains(*row)));
+    }
+
+    /// Element `width` wide and 10 high that counts how often it is asked
+    /// for its intrinsic sizes.
+    struct Measured {
+        width: f32,
+        measures: Rc<Cell<usize>>,
+    }
+
+    impl Measured {
+        fn measure(&self, size: f32) -> Result<f32, SurfaceError> {
+            self.measures.set(self.measures.get() + 1);
+            Ok(size)
+        }
+    }
+
+    #[allow(unused)]
+    impl LayoutElement for Measured {
+        fn layout(&mut self, constraint: Constraint, context: &mut LayoutContext, children: &mut Vec<ElementId>, requested: Option<Size>) -> Result<LayoutResult, SurfaceError> {
+            Ok(LayoutResult::Done(constraint.constrain(Size::new(self.width, 10.0))))
+        }
+
+        fn min_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+            self.measure(self.width)
+        }
+
+        fn max_intrinsic_width(&self, height: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+            self.measure(self.width)
+        }
+
+        fn min_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+            self.measure(10.0)
+        }
+
+        fn max_intrinsic_height(&self, width: f32, context: &LayoutContext, children: &Vec<ElementId>) -> Result<f32, SurfaceError> {
+            self.measure(10.0)
+        }
+    }
+
+    /// Flex elements nested `depth` deep with alternating axes around a
+    /// `Measured` element.
+    fn nested_flex(surface: &mut Surface, depth: usize, measures: Rc<Cell<usize>>) -> ElementId {
+        let root = surface.insert(Box::new(FlexElement::new(Axis::Horizontal)));
+        surface.set_root(root).unwrap();
+        let mut parent = root;
+        for level in 0..depth {
+            let axis = if level % 2 == 0 { Axis::Vertical } else { Axis::Horizontal };
+            let flex = surface.insert(Box::new(FlexElement::new(axis)));
+            surface.set_parent(flex, parent).unwrap();
+            parent = flex;
+        }
+        let leaf = surface.insert(Box::new(Measured { width: 30.0, measures }));
+        surface.set_parent(leaf, parent).unwrap();
+        leaf
+    }
+
+    #[test]
+    fn nested_intrinsic_sizes_are_measured_once() {
+        let mut surface = new_surface();
+        let measures = Rc::new(Cell::new(0));
+        let leaf = nested_flex(&mut surface, 20, measures.clone());
+
+        surface.layout().unwrap();
+        // without the cache every level would multiply the calls
+        assert!(measures.get() < 100);
+        assert_eq!(surface.layout_context.get_rect(leaf.index()).width, 30.0);
+    }
+
+    #[test]
+    fn dirty_elements_are_measured_again() {
+        let mut surface = new_surface();
+        let measures = Rc::new(Cell::new(0));
+        let leaf = nested_flex(&mut surface, 4, measures.clone());
+        surface.layout().unwrap();
+
+        surface.get_element_mut::<Measured>(leaf).unwrap().width = 60.0;
+        surface.layout().unwrap();
+        assert_eq!(surface.layout_context.get_rect(leaf.index()).width, 60.0);
+    }
+
+    #[test]
+    fn clean_elements_keep_their_intrinsic_sizes() {
+        let mut surface = new_surface();
+        let measures = Rc::new(Cell::new(0));
+        let leaf = nested_flex(&mut surface, 1, measures.clone());
+        let sibling = surface.insert(Box::new(BoxElement::new()));
+        surface.set_parent(sibling, surface.get_parent(leaf).unwrap().unwrap()).unwrap();
+        surface.layout().unwrap();
+
+        // laying out the leaf dropped what was measured before
+        surface.invalidate(sibling).unwrap();
+        surface.layout().unwrap();
+        measures.set(0);
+
+        surface.invalidate(sibling).unwrap();
+        surface.layout().unwrap();
+        assert_eq!(measures.get(), 0);
     }
 }
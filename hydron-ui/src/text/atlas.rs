@@ -0,0 +1,164 @@
+use ::rect::*;
+use ::rusttype::{point, GlyphId, Scale};
+use ::size::*;
+use ::std::collections::HashMap;
+use super::font::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: FontId,
+    glyph: u32,
+    size: u32,
+}
+
+/// Glyph rasterized into a `GlyphAtlas`, in pixels.
+#[derive(Debug, Copy, Clone)]
+pub struct AtlasGlyph {
+    /// Part of the atlas the glyph is in.
+    pub rect: Rect,
+    /// Position of the top left corner of `rect` relative to the origin of
+    /// the glyph on the baseline.
+    pub offset: (f32, f32),
+}
+
+/// Row of glyphs in the atlas, filled from left to right.
+struct Shelf {
+    y: u32,
+    height: u32,
+    x: u32,
+}
+
+/// Coverage of glyphs packed into a single texture, one byte per pixel.
+/// Glyphs are rasterized for a whole pixel position the first time they are
+/// requested. Once the atlas is full it is cleared and filled again with
+/// the glyphs requested from then on.
+pub struct GlyphAtlas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    shelves: Vec<Shelf>,
+    glyphs: HashMap<GlyphKey, Option<AtlasGlyph>>,
+    dirty: Option<(u32, u32)>,
+}
+
+impl GlyphAtlas {
+    pub fn new(width: u32, height: u32) -> GlyphAtlas {
+        GlyphAtlas {
+            width,
+            height,
+            pixels: vec![0; (width * height) as usize],
+            shelves: vec![],
+            glyphs: HashMap::new(),
+            dirty: None,
+        }
+    }
+
+    pub fn get_size(&self) -> Size {
+        Size::new(self.width as f32, self.height as f32)
+    }
+
+    /// Rows of the atlas, from top to bottom.
+    pub fn get_pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Rows that changed since the last call, as first row and number of
+    /// rows.
+    pub fn take_dirty(&mut self) -> Option<(u32, u32)> {
+        self.dirty.take().map(|(start, end)| (start, end - start))
+    }
+
+    pub fn clear(&mut self) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel = 0;
+        }
+        self.shelves.clear();
+        self.glyphs.clear();
+        self.dirty = Some((0, self.height));
+    }
+
+    /// Glyph `glyph` of the font `font` at `size` pixels, rasterized into the
+    /// atlas if it is not in it yet. Glyphs without pixels, like spaces, and
+    /// glyphs larger than the atlas have none.
+    pub fn get_glyph(&mut self, fonts: &Fonts, font: FontId, glyph: u32, size: f32) -> Option<AtlasGlyph> {
+        let key = GlyphKey {
+            font,
+            glyph,
+            size: size.to_bits(),
+        };
+        if let Some(cached) = self.glyphs.get(&key) {
+            return *cached;
+        }
+
+        let rasterized = fonts.get(font).and_then(|font| {
+            let glyph = font.glyph(GlyphId(glyph)).scaled(Scale::uniform(size)).positioned(point(0.0, 0.0));
+            glyph.pixel_bounding_box().map(|bounds| (glyph, bounds))
+        });
+        let (glyph, bounds) = match rasterized {
+            Some(rasterized) => rasterized,
+            None => {
+                self.glyphs.insert(key, None);
+                return None;
+            },
+        };
+
+        let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+        if width + 1 > self.width || height + 1 > self.height {
+            self.glyphs.insert(key, None);
+            return None;
+        }
+        let (x, y) = match self.allocate(width, height) {
+            Some(position) => position,
+            None => {
+                self.clear();
+                self.allocate(width, height)?
+            },
+        };
+
+        let stride = self.width;
+        let pixels = &mut self.pixels;
+        glyph.draw(|gx, gy, coverage| {
+            pixels[((y + gy) * stride + x + gx) as usize] = (coverage * 255.0).round() as u8;
+        });
+        self.dirty = Some(match self.dirty {
+            Some((start, end)) => (start.min(y), end.max(y + height)),
+            None => (y, y + height),
+        });
+
+        let atlas_glyph = AtlasGlyph {
+            rect: Rect::new(x as f32, y as f32, width as f32, height as f32),
+            offset: (bounds.min.x as f32, bounds.min.y as f32),
+        };
+        self.glyphs.insert(key, Some(atlas_glyph));
+        Some(atlas_glyph)
+    }
+
+    /// Finds room for `width` by `height` pixels, keeping a pixel free
+    /// around each glyph.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let (padded_width, padded_height) = (width + 1, height + 1);
+        if padded_width > self.width {
+            return None;
+        }
+
+        let atlas_width = self.width;
+        let shelf = self.shelves.iter_mut()
+            .find(|shelf| shelf.height >= padded_height && shelf.height <= padded_height * 2 && shelf.x + padded_width <= atlas_width);
+        if let Some(shelf) = shelf {
+            let x = shelf.x;
+            shelf.x += padded_width;
+            return Some((x, shelf.y));
+        }
+
+        let y = self.shelves.last().map(|shelf| shelf.y + shelf.height).unwrap_or(0);
+        if y + padded_height > self.height {
+            return None;
+        }
+        self.shelves.push(Shelf {
+            y,
+            height: padded_height,
+            x: padded_width,
+        });
+        Some((0, y))
+    }
+}
@@ -0,0 +1,105 @@
+use ::std::ops::Range;
+use ::unicode_bidi::{BidiInfo, Level};
+use super::shaping::*;
+
+/// Directions of the parts of a text, from the Unicode bidirectional
+/// algorithm.
+pub struct Bidi<'a> {
+    info: BidiInfo<'a>,
+}
+
+impl<'a> Bidi<'a> {
+    /// Runs the algorithm on `text`, with paragraphs that are right to left
+    /// if `rtl`.
+    pub fn new(text: &'a str, rtl: bool) -> Bidi<'a> {
+        let level = if rtl { Level::rtl() } else { Level::ltr() };
+        Bidi {
+            info: BidiInfo::new(text, Some(level)),
+        }
+    }
+
+    /// Parts of `range` with a single direction, in the order of the text,
+    /// and whether they are right to left.
+    pub fn runs(&self, range: Range<usize>) -> Vec<(Range<usize>, bool)> {
+        let levels = &self.info.levels;
+        let mut runs = vec![];
+        let mut start = range.start;
+        for index in range.start..range.end {
+            if index > start && levels[index] != levels[start] {
+                runs.push((start..index, levels[start].is_rtl()));
+                start = index;
+            }
+        }
+        if start < range.end {
+            runs.push((start..range.end, levels[start].is_rtl()));
+        }
+        runs
+    }
+
+    /// Glyphs of one line, given in the order of the text, in the order they
+    /// are shown from left to right and placed after each other.
+    pub fn reorder(&self, glyphs: &[ShapedGlyph]) -> Vec<ShapedGlyph> {
+        let text = self.info.text;
+        let (start, end) = match (glyphs.iter().map(|glyph| glyph.index).min(), glyphs.iter().map(|glyph| glyph.index).max()) {
+            (Some(start), Some(end)) => (start, end + text[end..].chars().next().map_or(0, char::len_utf8)),
+            _ => return vec![],
+        };
+        let paragraph = match self.info.paragraphs.iter().find(|paragraph| paragraph.range.start <= start && start < paragraph.range.end) {
+            Some(paragraph) => paragraph,
+            None => return glyphs.to_vec(),
+        };
+
+        let (levels, runs) = self.info.visual_runs(paragraph, start..end.min(paragraph.range.end));
+        let mut ordered = Vec::with_capacity(glyphs.len());
+        for run in runs {
+            let mut part: Vec<ShapedGlyph> = glyphs.iter().filter(|glyph| run.start <= glyph.index && glyph.index < run.end).cloned().collect();
+            if levels[run.start].is_rtl() {
+                part.reverse();
+            }
+            ordered.extend(part);
+        }
+        // glyphs of a line that runs into the next paragraph stay at the end
+        ordered.extend(glyphs.iter().filter(|glyph| glyph.index >= paragraph.range.end).cloned());
+        place(&mut ordered);
+        ordered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Glyphs 10 wide for every character of `text`, in its order.
+    fn glyphs(text: &str) -> Vec<ShapedGlyph> {
+        let mut glyphs: Vec<ShapedGlyph> = text.char_indices()
+            .map(|(index, _)| ShapedGlyph { id: 1, x: 0.0, advance: 10.0, index })
+            .collect();
+        place(&mut glyphs);
+        glyphs
+    }
+
+    #[test]
+    fn runs_split_at_direction_changes() {
+        let text = "ab \u{5d0}\u{5d1} cd";
+        let bidi = Bidi::new(text, false);
+        assert_eq!(bidi.runs(0..text.len()), vec![(0..3, false), (3..7, true), (7..10, false)]);
+        assert_eq!(bidi.runs(4..9), vec![(4..7, true), (7..9, false)]);
+    }
+
+    #[test]
+    fn right_to_left_runs_are_reversed() {
+        let text = "ab \u{5d0}\u{5d1} cd";
+        let ordered = Bidi::new(text, false).reorder(&glyphs(text));
+        let indices: Vec<usize> = ordered.iter().map(|glyph| glyph.index).collect();
+        assert_eq!(indices, vec![0, 1, 2, 5, 3, 7, 8, 9]);
+        assert_eq!(ordered[3].x, 30.0);
+    }
+
+    #[test]
+    fn right_to_left_paragraphs_put_left_to_right_runs_in_reverse() {
+        let text = "\u{5d0} ab cd";
+        let ordered = Bidi::new(text, true).reorder(&glyphs(text));
+        let indices: Vec<usize> = ordered.iter().map(|glyph| glyph.index).collect();
+        assert_eq!(indices, vec![3, 4, 5, 6, 7, 2, 0]);
+    }
+}
@@ -0,0 +1,203 @@
+use ::rusttype;
+use ::rusttype::{Font, FontCollection, Scale};
+use ::std::error::Error;
+use ::std::fmt;
+use ::std::fs::File;
+use ::std::io;
+use ::std::io::Read;
+use ::std::ops::Range;
+use ::std::path::Path;
+use super::shaping::*;
+
+/// Handle to a font loaded into `Fonts`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FontId {
+    index: usize,
+}
+
+impl FontId {
+    pub(crate) fn new(index: usize) -> FontId {
+        FontId {
+            index,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(io::Error),
+    Parse(rusttype::Error),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FontError::Io(error) => write!(f, "could not read font: {}", error),
+            FontError::Parse(error) => write!(f, "could not parse font: {}", error),
+        }
+    }
+}
+
+impl Error for FontError {
+}
+
+impl From<io::Error> for FontError {
+    fn from(error: io::Error) -> FontError {
+        FontError::Io(error)
+    }
+}
+
+impl From<rusttype::Error> for FontError {
+    fn from(error: rusttype::Error) -> FontError {
+        FontError::Parse(error)
+    }
+}
+
+/// Weight of a font face, from 100 for thin to 900 for black.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FontWeight(pub u16);
+
+impl FontWeight {
+    pub const NORMAL: FontWeight = FontWeight(400);
+    pub const BOLD: FontWeight = FontWeight(700);
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+}
+
+/// What a loaded font is a face of.
+struct Face {
+    family: Option<String>,
+    weight: FontWeight,
+    style: FontStyle,
+}
+
+/// Vertical metrics of a font at a size, in the units of the size.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FontMetrics {
+    /// Distance from the baseline to the top of the highest glyph.
+    pub ascent: f32,
+    /// Distance from the baseline to the bottom of the lowest glyph.
+    pub descent: f32,
+    /// Space the font suggests between the descent of a line and the ascent
+    /// of the next.
+    pub line_gap: f32,
+}
+
+impl FontMetrics {
+    pub fn line_height(&self) -> f32 {
+        self.ascent + self.descent + self.line_gap
+    }
+}
+
+/// Fonts text is laid out and drawn with. The first font loaded is the
+/// default font.
+///
+/// Fonts can be grouped into families with `set_face`, text then picks the
+/// face of a family by weight and style. Missing faces are not synthesized,
+/// the closest face is used instead.
+pub struct Fonts {
+    fonts: Vec<Font<'static>>,
+    faces: Vec<Face>,
+}
+
+impl Fonts {
+    pub fn new() -> Fonts {
+        Fonts {
+            fonts: vec![],
+            faces: vec![],
+        }
+    }
+
+    /// Loads a TrueType font, or an OpenType font with TrueType outlines.
+    /// Of a collection only the first font is loaded.
+    pub fn load_bytes(&mut self, bytes: Vec<u8>) -> Result<FontId, FontError> {
+        // rusttype reads the tag of a collection without checking the length
+        if bytes.len() < 4 {
+            return Err(FontError::Parse(rusttype::Error::UnrecognizedFormat));
+        }
+        let font = FontCollection::from_bytes(bytes)?.font_at(0)?;
+        self.fonts.push(font);
+        self.faces.push(Face {
+            family: None,
+            weight: FontWeight::NORMAL,
+            style: FontStyle::Normal,
+        });
+        Ok(FontId::new(self.fonts.len() - 1))
+    }
+
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<FontId, FontError> {
+        let mut bytes = vec![];
+        File::open(path)?.read_to_end(&mut bytes)?;
+        self.load_bytes(bytes)
+    }
+
+    pub fn get(&self, id: FontId) -> Option<&Font<'static>> {
+        self.fonts.get(id.index())
+    }
+
+    pub fn get_default(&self) -> Option<FontId> {
+        if self.fonts.is_empty() {
+            None
+        } else {
+            Some(FontId::new(0))
+        }
+    }
+
+    /// Makes `id` the face of `family` with `weight` and `style`.
+    pub fn set_face(&mut self, id: FontId, family: &str, weight: FontWeight, style: FontStyle) {
+        if let Some(face) = self.faces.get_mut(id.index()) {
+            face.family = Some(family.to_string());
+            face.weight = weight;
+            face.style = style;
+        }
+    }
+
+    /// Weight and style of the face `id` is, see `set_face`.
+    pub fn get_face(&self, id: FontId) -> Option<(FontWeight, FontStyle)> {
+        self.faces.get(id.index()).map(|face| (face.weight, face.style))
+    }
+
+    /// Face of the family of `id` that is closest to `weight` and `style`.
+    /// Faces with the requested style win over faces with a closer weight.
+    /// A font without a family is its only face.
+    pub fn select(&self, id: FontId, weight: FontWeight, style: FontStyle) -> FontId {
+        let family = match self.faces.get(id.index()).and_then(|face| face.family.as_ref()) {
+            Some(family) => family,
+            None => return id,
+        };
+        self.faces.iter()
+            .enumerate()
+            .filter(|&(_, face)| face.family.as_ref() == Some(family))
+            .min_by_key(|&(_, face)| (face.style != style, (face.weight.0 as i32 - weight.0 as i32).abs()))
+            .map(|(index, _)| FontId::new(index))
+            .unwrap_or(id)
+    }
+
+    pub fn metrics(&self, id: FontId, size: f32) -> Option<FontMetrics> {
+        self.get(id).map(|font| {
+            let metrics = font.v_metrics(Scale::uniform(size));
+            FontMetrics {
+                ascent: metrics.ascent,
+                descent: -metrics.descent,
+                line_gap: metrics.line_gap,
+            }
+        })
+    }
+
+    /// Glyphs of the part `range` of `text` in the font `id` at `size`, see
+    /// `shape`.
+    pub fn shape(&self, id: FontId, size: f32, text: &str, range: Range<usize>, rtl: bool) -> Vec<ShapedGlyph> {
+        match self.get(id) {
+            Some(font) => shape(font, size, text, range, rtl),
+            None => vec![],
+        }
+    }
+}
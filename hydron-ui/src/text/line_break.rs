@@ -0,0 +1,166 @@
+use ::std::ops::Range;
+use ::xi_unicode::LineBreakIterator;
+use super::shaping::*;
+
+fn is_whitespace(text: &str, glyph: &ShapedGlyph) -> bool {
+    text[glyph.index..].chars().next().map_or(false, char::is_whitespace)
+}
+
+/// Width of `glyphs`, shaped from `text`, from the origin of the first to
+/// the end of the last, leaving out whitespace at the end.
+pub fn line_width(text: &str, glyphs: &[ShapedGlyph]) -> f32 {
+    match glyphs.iter().rposition(|glyph| !is_whitespace(text, glyph)) {
+        Some(last) => glyphs[last].x + glyphs[last].advance - glyphs[0].x,
+        None => 0.0,
+    }
+}
+
+/// Drops whitespace from the end of `glyphs`, it takes no room at the end of
+/// a line.
+pub fn trim_end(text: &str, glyphs: &mut Vec<ShapedGlyph>) {
+    while glyphs.last().map_or(false, |glyph| is_whitespace(text, glyph)) {
+        glyphs.pop();
+    }
+}
+
+/// Drops glyphs from the end of `glyphs` until they are at most `max_width`
+/// wide, then the whitespace they end in, e.g. to make room for an ellipsis.
+pub fn truncate(text: &str, glyphs: &mut Vec<ShapedGlyph>, max_width: f32) {
+    while !glyphs.is_empty() && line_width(text, glyphs) > max_width {
+        glyphs.pop();
+    }
+    trim_end(text, glyphs);
+}
+
+/// Breaks `glyphs`, shaped from `text`, into lines no wider than
+/// `max_width`, as ranges of glyphs. Lines end after hard breaks like
+/// newlines and otherwise at the last break opportunity of the Unicode line
+/// breaking algorithm that fits. Words wider than a line are broken between
+/// glyphs, a single glyph wider than a line overflows it. Empty text has no
+/// lines.
+pub fn break_lines(text: &str, glyphs: &[ShapedGlyph], max_width: f32) -> Vec<Range<usize>> {
+    let mut lines = vec![];
+    let mut start = 0;
+    let mut end = 0;
+    let mut candidate: Option<usize> = None;
+    for (offset, hard) in LineBreakIterator::new(text) {
+        while end < glyphs.len() && glyphs[end].index < offset {
+            end += 1;
+        }
+
+        while line_width(text, &glyphs[start..end]) > max_width {
+            match candidate {
+                Some(candidate) if candidate > start => {
+                    lines.push(start..candidate);
+                    start = candidate;
+                },
+                _ => {
+                    let split = (start + 1..end).rev()
+                        .find(|split| line_width(text, &glyphs[start..*split]) <= max_width)
+                        .unwrap_or(start + 1);
+                    if split >= end {
+                        break;
+                    }
+                    lines.push(start..split);
+                    start = split;
+                },
+            }
+            candidate = None;
+        }
+
+        if hard {
+            lines.push(start..end);
+            start = end;
+            candidate = None;
+        } else {
+            candidate = Some(end);
+        }
+    }
+    lines
+}
+
+/// Width of the widest part of `glyphs` between two break opportunities,
+/// the narrowest the text can get without breaking words.
+pub fn min_content_width(text: &str, glyphs: &[ShapedGlyph]) -> f32 {
+    let mut width: f32 = 0.0;
+    let mut start = 0;
+    let mut end = 0;
+    for (offset, _) in LineBreakIterator::new(text) {
+        while end < glyphs.len() && glyphs[end].index < offset {
+            end += 1;
+        }
+        width = width.max(line_width(text, &glyphs[start..end]));
+        start = end;
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One glyph 10 wide per character, control characters get none like
+    /// in `shape`.
+    fn glyphs(text: &str) -> Vec<ShapedGlyph> {
+        let mut glyphs: Vec<ShapedGlyph> = text.char_indices()
+            .filter(|&(_, c)| !c.is_control())
+            .map(|(index, _)| ShapedGlyph { id: 1, x: 0.0, advance: 10.0, index })
+            .collect();
+        place(&mut glyphs);
+        glyphs
+    }
+
+    fn lines(text: &str, max_width: f32) -> Vec<Range<usize>> {
+        break_lines(text, &glyphs(text), max_width)
+    }
+
+    #[test]
+    fn empty_text_has_no_lines() {
+        assert!(lines("", 100.0).is_empty());
+    }
+
+    #[test]
+    fn lines_end_after_hard_breaks() {
+        assert_eq!(lines("ab\ncd", 100.0), vec![0..2, 2..4]);
+        assert_eq!(lines("ab\n\ncd", 100.0), vec![0..2, 2..2, 2..4]);
+    }
+
+    #[test]
+    fn lines_break_at_the_last_opportunity_that_fits() {
+        assert_eq!(lines("ab cd ef", 55.0), vec![0..6, 6..8]);
+        assert_eq!(lines("ab cd ef", 100.0), vec![0..8]);
+    }
+
+    #[test]
+    fn whitespace_at_the_end_of_a_line_may_overflow() {
+        assert_eq!(lines("ab cd", 20.0), vec![0..3, 3..5]);
+    }
+
+    #[test]
+    fn long_words_break_between_glyphs() {
+        assert_eq!(lines("abcdef", 25.0), vec![0..2, 2..4, 4..6]);
+        assert_eq!(lines("ab cdefgh", 35.0), vec![0..3, 3..6, 6..9]);
+    }
+
+    #[test]
+    fn glyphs_wider_than_a_line_overflow_it() {
+        assert_eq!(lines("ab", 5.0), vec![0..1, 1..2]);
+    }
+
+    #[test]
+    fn min_content_width_is_the_widest_word() {
+        let text = "ab cdef g";
+        assert_eq!(min_content_width(text, &glyphs(text)), 40.0);
+        assert_eq!(min_content_width("", &[]), 0.0);
+    }
+
+    #[test]
+    fn truncate_makes_room_and_drops_whitespace() {
+        let text = "ab cd";
+        let mut line = glyphs(text);
+        truncate(text, &mut line, 35.0);
+        assert_eq!(line.iter().map(|glyph| glyph.index).collect::<Vec<_>>(), vec![0, 1]);
+        truncate(text, &mut line, 5.0);
+        assert!(line.is_empty());
+    }
+}
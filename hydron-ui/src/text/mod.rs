@@ -0,0 +1,13 @@
+mod atlas;
+mod bidi;
+mod font;
+mod line_break;
+mod shaping;
+mod span;
+
+pub use self::atlas::*;
+pub use self::bidi::*;
+pub use self::font::*;
+pub use self::line_break::*;
+pub use self::shaping::*;
+pub use self::span::*;
@@ -0,0 +1,57 @@
+use ::rusttype::{Font, GlyphId, Scale};
+use ::std::ops::Range;
+
+/// Glyph placed on the baseline of a run, in the units of the font size.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ShapedGlyph {
+    pub id: u32,
+    /// Distance of the origin of the glyph from the start of the run.
+    pub x: f32,
+    pub advance: f32,
+    /// Byte offset of the character in the text the glyph belongs to.
+    pub index: usize,
+}
+
+/// Maps the characters in the part `range` of `text` to glyphs of `font` at
+/// `size`. Glyphs come in the order of the text, for right to left text that
+/// is the reverse of the order they are shown in, and are placed after each
+/// other in that order. The advance of every glyph includes the kerning with
+/// the glyph shown right of it. Control characters get no glyph. There are
+/// no ligatures, and characters the font has no glyph for get its missing
+/// glyph.
+pub fn shape(font: &Font, size: f32, text: &str, range: Range<usize>, rtl: bool) -> Vec<ShapedGlyph> {
+    let scale = Scale::uniform(size);
+    let mut glyphs: Vec<ShapedGlyph> = vec![];
+    for (index, character) in text[range.clone()].char_indices() {
+        if character.is_control() {
+            continue;
+        }
+        let glyph = font.glyph(character).scaled(scale);
+        let id = glyph.id();
+        let mut advance = glyph.h_metrics().advance_width;
+        if let Some(previous) = glyphs.last_mut() {
+            if rtl {
+                advance += font.pair_kerning(scale, id, GlyphId(previous.id));
+            } else {
+                previous.advance += font.pair_kerning(scale, GlyphId(previous.id), id);
+            }
+        }
+        glyphs.push(ShapedGlyph {
+            id: id.0,
+            x: 0.0,
+            advance,
+            index: range.start + index,
+        });
+    }
+    place(&mut glyphs);
+    glyphs
+}
+
+/// Places `glyphs` after each other, starting at 0.
+pub fn place(glyphs: &mut [ShapedGlyph]) {
+    let mut x = 0.0;
+    for glyph in glyphs.iter_mut() {
+        glyph.x = x;
+        x += glyph.advance;
+    }
+}
@@ -0,0 +1,78 @@
+use ::color::*;
+use ::style::*;
+use super::font::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TextDecoration {
+    None,
+    Underline,
+    LineThrough,
+}
+
+/// Part of a paragraph with its own style. The font and color are taken
+/// from the `TextElement` unless set, the size defaults to the font size of
+/// the element and the weight and style to those of the face of the font.
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub text: String,
+    pub font: Option<FontId>,
+    /// Percentages and `Length::Em` are relative to the font size of the
+    /// element.
+    pub font_size: Option<Length>,
+    pub weight: Option<FontWeight>,
+    pub style: Option<FontStyle>,
+    pub color: Option<Rgba>,
+    pub decoration: TextDecoration,
+    /// Target of the span, e.g. a URL, see `TextElement::span_at`.
+    pub link: Option<String>,
+}
+
+impl Span {
+    pub fn new<S: Into<String>>(text: S) -> Span {
+        Span {
+            text: text.into(),
+            font: None,
+            font_size: None,
+            weight: None,
+            style: None,
+            color: None,
+            decoration: TextDecoration::None,
+            link: None,
+        }
+    }
+
+    pub fn with_font(mut self, font: FontId) -> Span {
+        self.font = Some(font);
+        self
+    }
+
+    pub fn with_font_size(mut self, font_size: Length) -> Span {
+        self.font_size = Some(font_size);
+        self
+    }
+
+    pub fn with_weight(mut self, weight: FontWeight) -> Span {
+        self.weight = Some(weight);
+        self
+    }
+
+    pub fn with_style(mut self, style: FontStyle) -> Span {
+        self.style = Some(style);
+        self
+    }
+
+    pub fn with_color(mut self, color: Rgba) -> Span {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn with_decoration(mut self, decoration: TextDecoration) -> Span {
+        self.decoration = decoration;
+        self
+    }
+
+    pub fn with_link<S: Into<String>>(mut self, link: S) -> Span {
+        self.link = Some(link.into());
+        self
+    }
+}
@@ -0,0 +1,89 @@
+use gl;
+use gl::types::*;
+use image;
+use image::{DynamicImage, ImageResult};
+
+use std::os::raw::c_void;
+use std::path::Path;
+use hydron_ui::{ImageId, Size};
+
+/// Decoded image uploaded to a texture.
+pub struct Image {
+    pub texture: GLuint,
+    /// Size in pixels.
+    pub size: Size,
+}
+
+/// Decodes images into textures and hands out the ids `Background::Image`
+/// refers to them by. The GL context has to be current while loading.
+pub struct ImageRegistry {
+    images: Vec<Image>,
+}
+
+impl ImageRegistry {
+    pub fn new() -> ImageRegistry {
+        ImageRegistry {
+            images: vec![],
+        }
+    }
+
+    /// Decodes a PNG or JPEG image from `bytes`.
+    pub fn load_bytes(&mut self, bytes: &[u8]) -> ImageResult<ImageId> {
+        let image = image::load_from_memory(bytes)?;
+        Ok(self.insert(image))
+    }
+
+    /// Decodes a PNG or JPEG file, the format is guessed from the extension.
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> ImageResult<ImageId> {
+        let image = image::open(path)?;
+        Ok(self.insert(image))
+    }
+
+    pub fn get(&self, id: ImageId) -> Option<&Image> {
+        self.images.get(id.index())
+    }
+
+    fn insert(&mut self, image: DynamicImage) -> ImageId {
+        let image = image.to_rgba();
+        let (width, height) = image.dimensions();
+        let pixels = image.into_raw();
+
+        let mut texture = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA8 as GLint,
+                width as GLsizei,
+                height as GLsizei,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr() as *const c_void
+            );
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+
+        self.images.push(Image {
+            texture,
+            size: Size::new(width as f32, height as f32),
+        });
+        ImageId::new(self.images.len() - 1)
+    }
+}
+
+impl Drop for ImageRegistry {
+    fn drop(&mut self) {
+        for image in self.images.iter() {
+            unsafe {
+                gl::DeleteTextures(1, &image.texture);
+            }
+        }
+    }
+}
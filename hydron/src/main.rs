@@ -5,10 +5,12 @@ extern crate libc;
 extern crate gl;
 extern crate glutin;
 extern crate cgmath;
+extern crate image;
 
 extern crate hydron_ui;
 extern crate hydron_template;
 
+mod images;
 mod renderer;
 
 use glutin::dpi::*;
@@ -18,6 +20,7 @@ use hydron_ui::elements::*;
 use hydron_ui::style::*;
 use hydron_template::template;
 
+use images::*;
 use renderer::*;
 
 #[derive(Debug)]
@@ -62,44 +65,48 @@ fn main() {
         gl::ClearColor(1.0, 1.0, 1.0, 1.0);
     }
 
+    let images = ImageRegistry::new();
     let mut renderer = Renderer::new();
-    let mut surface = Surface::new(Size::new(1024, 768));
+    let mut surface = Surface::new(Size::new(1024.0, 768.0));
+    surface.set_scale_factor(gl_window.get_hidpi_factor() as f32);
 
     let root_index = surface.insert(Box::new(BoxElement::new()));
-    surface.set_root(root_index);
-    surface.set_style(root_index, Style::new(Background::Color(Rgba::new(1.0, 1.0, 1.0, 1.0)), TRBL::new(Border::None, Border::None, Border::None, Border::None)));
+    surface.set_root(root_index).unwrap();
+    surface.set_style(root_index, Style::new(Background::Color(Rgba::new(1.0, 1.0, 1.0, 1.0)), TRBL::all(Border::none()))).unwrap();
 
-    let index_0 = surface.insert(Box::new(FlexElement::new(Axis::Horizontal)));
-    surface.set_parent(index_0, root_index);
+    let index_0 = surface.insert(Box::new(FlexElement::new(Axis::Horizontal)
+        .with_justify_content(Justify::SpaceAround)
+        .with_align_items(Align::Start)));
+    surface.set_parent(index_0, root_index).unwrap();
 
-    let index_1 = surface.insert(Box::new(FixedElement::new(200, 100)));
+    let index_1 = surface.insert(Box::new(FixedElement::new(200.0, 100.0)));
     surface.set_style(index_1, Style::new(
         Background::Color(Rgba::new(1.0, 0.0, 0.0, 1.0)),
-        TRBL::new(Border::None, Border::None, Border::None, Border::None)
-    ));
+        TRBL::all(Border::none())
+    )).unwrap();
 
-    let index_2 = surface.insert(Box::new(FixedElement::new(200, 200)));
+    let index_2 = surface.insert(Box::new(FixedElement::new(200.0, 200.0)));
     surface.set_style(index_2, Style::new(
         Background::Color(Rgba::new(0.0, 1.0, 0.0, 1.0)),
-        TRBL::new(Border::None, Border::None, Border::None, Border::None)
-    ));
+        TRBL::all(Border::none())
+    )).unwrap();
 
-    let index_3 = surface.insert(Box::new(FixedElement::new(200, 300)));
+    let index_3 = surface.insert(Box::new(FixedElement::new(200.0, 300.0)));
     surface.set_style(index_3, Style::new(
         Background::Color(Rgba::new(0.0, 0.0, 1.0, 1.0)),
-        TRBL::new(Border::None, Border::None, Border::None, Border::None)
-    ));
+        TRBL::all(Border::none())
+    )).unwrap();
 
-    let index_4 = surface.insert(Box::new(FixedElement::new(200, 400)));
+    let index_4 = surface.insert(Box::new(FixedElement::new(200.0, 400.0)));
     surface.set_style(index_4, Style::new(
         Background::Color(Rgba::new(0.5, 0.5, 0.5, 1.0)),
-        TRBL::new(Border::None, Border::None, Border::None, Border::None)
-    ));
+        TRBL::all(Border::none())
+    )).unwrap();
 
-    surface.set_parent(index_1, index_0);
-    surface.set_parent(index_2, index_0);
-    surface.set_parent(index_3, index_0);
-    surface.set_parent(index_4, index_0);
+    surface.set_parent(index_1, index_0).unwrap();
+    surface.set_parent(index_2, index_0).unwrap();
+    surface.set_parent(index_3, index_0).unwrap();
+    surface.set_parent(index_4, index_0).unwrap();
 
     let mut running = true;
     while running {
@@ -110,7 +117,8 @@ fn main() {
                     glutin::WindowEvent::Resized(logical_size) => {
                         let dpi_factor = gl_window.get_hidpi_factor();
                         gl_window.resize(logical_size.to_physical(dpi_factor));
-                        let new_size = Size::new(logical_size.width as usize, logical_size.height as usize);
+                        let new_size = Size::new(logical_size.width as f32, logical_size.height as f32);
+                        surface.set_scale_factor(dpi_factor as f32);
                         surface.resize(new_size);
                     },
                     _ => ()
@@ -119,7 +127,7 @@ fn main() {
             }
         });
 
-        surface.layout();
+        surface.layout().unwrap();
         let commands = surface.paint();
 
         unsafe {
@@ -127,7 +135,9 @@ fn main() {
             gl::Clear(gl::COLOR_BUFFER_BIT);
         }
 
-        renderer.render(surface.get_size(), commands);
+        let size = surface.get_size();
+        let scale_factor = surface.get_scale_factor();
+        renderer.render(Size::new(size.width * scale_factor, size.height * scale_factor), commands, &images, surface.get_fonts());
 
         gl_window.swap_buffers().unwrap();
     }
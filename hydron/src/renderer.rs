@@ -6,8 +6,12 @@ use std::ptr;
 use std::str;
 use std::mem;
 use std::os::raw::c_void;
-use hydron_ui::rendering::Command;
-use hydron_ui::Size;
+use hydron_ui::rendering::{Command, Gradient, Side};
+use hydron_ui::rendering::image::image_rects;
+use hydron_ui::style::{stop_chunks, Corners, ImageFit, TRBL, MAX_GRADIENT_STOPS};
+use hydron_ui::text::{Fonts, GlyphAtlas};
+use images::ImageRegistry;
+use hydron_ui::{Rect, Rgba, Size};
 
 static VS_SRC: &'static str = r#"
     #version 330 core
@@ -17,16 +21,240 @@ static VS_SRC: &'static str = r#"
     }
 "#;
 
+// Shapes are described by their signed distance, edges are antialiased by
+// the coverage of the pixel. Rects are given as x, y, width and height in
+// window pixels with y pointing down, radii and widths in the order of
+// `Corners` and `TRBL`. Gradients are given as start and end point, or as
+// center and radius, with up to MAX_GRADIENT_STOPS stops that are only
+// drawn where the gradient is inside `stop_range`. A ring can be limited to one `side`, with every pixel
+// belonging to the side it is closest to relative to the width of the side,
+// and be broken into `dash` long dashes with gaps. Images are drawn into `shape`, with `source` being the
+// part of the texture to draw in texture coordinates. Text takes the coverage
+// of its glyphs from the red channel. Layers are premultiplied and cover the
+// whole window.
 static FS_SRC: &'static str = r#"
     #version 330 core
+    uniform int mode;
+    uniform vec2 viewport;
+    uniform vec4 rect;
+    uniform vec4 radii;
+    uniform vec4 widths;
+    uniform int side;
+    uniform vec2 dash;
+    uniform vec4 shape;
+    uniform vec4 shape_radii;
+    uniform float blur;
     uniform vec4 color;
+    uniform vec4 gradient;
+    uniform vec4 stop_colors[MAX_GRADIENT_STOPS];
+    uniform float stop_positions[MAX_GRADIENT_STOPS];
+    uniform int stop_count;
+    uniform vec2 stop_range;
+    uniform vec4 source;
+    uniform sampler2D image;
     out vec4 fragColor;
+
+    float rounded_box(vec2 p, vec4 box, vec4 corners) {
+        vec2 half_size = box.zw * 0.5;
+        vec2 q = p - box.xy - half_size;
+        float radius = q.x > 0.0 ? (q.y > 0.0 ? corners.z : corners.y) : (q.y > 0.0 ? corners.w : corners.x);
+        vec2 d = abs(q) - half_size + radius;
+        return min(max(d.x, d.y), 0.0) + length(max(d, 0.0)) - radius;
+    }
+
+    float coverage(float distance) {
+        return clamp(0.5 - distance, 0.0, 1.0);
+    }
+
+    float erf(float x) {
+        float s = sign(x);
+        float a = abs(x);
+        x = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+        x *= x;
+        return s - s / (x * x);
+    }
+
+    // coverage of a shape convolved with a gaussian, approximated along the
+    // distance to its edge
+    float blurred(float distance, float sigma) {
+        if (sigma < 0.5) {
+            return coverage(distance);
+        }
+        return 0.5 - 0.5 * erf(distance / (sigma * 1.4142135));
+    }
+
+    vec4 gradient_color(float t) {
+        vec4 result = stop_colors[0];
+        for (int i = 1; i < MAX_GRADIENT_STOPS; i++) {
+            if (i >= stop_count) {
+                break;
+            }
+            float start = stop_positions[i - 1];
+            float end = stop_positions[i];
+            if (t > start) {
+                float f = end > start ? clamp((t - start) / (end - start), 0.0, 1.0) : 1.0;
+                result = mix(stop_colors[i - 1], stop_colors[i], f);
+            }
+        }
+        return result;
+    }
+
     void main() {
-       fragColor = color;
+        vec2 p = vec2(gl_FragCoord.x, viewport.y - gl_FragCoord.y);
+        float alpha;
+        if (mode == 9) {
+            fragColor = texture(image, gl_FragCoord.xy / viewport) * color.a;
+            return;
+        }
+        if (mode >= 6) {
+            vec2 uv = source.xy + (p - shape.xy) / shape.zw * source.zw;
+            if (mode == 7) {
+                uv = fract(uv);
+            }
+            vec4 texel = texture(image, uv);
+            if (mode == 8) {
+                fragColor = vec4(color.rgb, color.a * texel.r);
+            } else {
+                fragColor = vec4(texel.rgb, texel.a * color.a * coverage(rounded_box(p, rect, radii)));
+            }
+            return;
+        }
+        if (mode == 4 || mode == 5) {
+            float t;
+            if (mode == 4) {
+                vec2 line = gradient.zw - gradient.xy;
+                float length_squared = dot(line, line);
+                t = length_squared > 0.0 ? dot(p - gradient.xy, line) / length_squared : 0.0;
+            } else {
+                t = gradient.z > 0.0 ? length(p - gradient.xy) / gradient.z : 1.0;
+            }
+            if (t < stop_range.x || t >= stop_range.y) {
+                discard;
+            }
+            vec4 fill = gradient_color(t);
+            fragColor = vec4(fill.rgb, fill.a * color.a * coverage(rounded_box(p, rect, radii)));
+            return;
+        }
+        if (mode == 0) {
+            alpha = coverage(rounded_box(p, rect, radii));
+        } else if (mode == 1) {
+            vec4 inner = vec4(rect.xy + widths.wx, rect.zw - widths.yx - widths.wz);
+            vec4 inner_radii = max(radii - max(widths.xxzz, widths.wyyw), 0.0);
+            alpha = coverage(rounded_box(p, rect, radii)) * (1.0 - coverage(rounded_box(p, inner, inner_radii)));
+            if (side >= 0) {
+                vec4 edges = vec4(p.y - rect.y, rect.x + rect.z - p.x, rect.y + rect.w - p.y, p.x - rect.x) / max(widths, 0.0001);
+                if (edges[side] > min(min(edges.x, edges.y), min(edges.z, edges.w))) {
+                    alpha = 0.0;
+                }
+                float along = side == 0 || side == 2 ? p.x - rect.x : p.y - rect.y;
+                if (dash.x > 0.0 && mod(along, dash.x + dash.y) >= dash.x) {
+                    alpha = 0.0;
+                }
+            }
+        } else if (mode == 2) {
+            alpha = blurred(rounded_box(p, shape, shape_radii), blur * 0.5);
+        } else {
+            alpha = coverage(rounded_box(p, rect, radii)) * (1.0 - blurred(rounded_box(p, shape, shape_radii), blur * 0.5));
+        }
+        fragColor = vec4(color.rgb, color.a * alpha);
     }
 "#;
+
+/// Shape drawn by the fragment shader, see `FS_SRC`.
+#[derive(Copy, Clone)]
+enum Mode {
+    Fill = 0,
+    Ring = 1,
+    Shadow = 2,
+    InsetShadow = 3,
+    LinearGradient = 4,
+    RadialGradient = 5,
+    Image = 6,
+    RepeatImage = 7,
+    Text = 8,
+    Layer = 9,
+}
+
+/// Offscreen target a group with an opacity is drawn into before it is
+/// blended into the target below as a whole.
+struct Layer {
+    framebuffer: GLuint,
+    texture: GLuint,
+    size: (GLsizei, GLsizei),
+}
+
+/// Width and height of the glyph atlas in pixels.
+const ATLAS_SIZE: u32 = 1024;
+
+/// Everything the fragment shader needs to draw one quad. Rects are in
+/// window pixels.
+struct Quad {
+    mode: Mode,
+    rect: Rect,
+    radii: Corners<f32>,
+    widths: TRBL<f32>,
+    side: Option<Side>,
+    dash: (f32, f32),
+    shape: Rect,
+    shape_radii: Corners<f32>,
+    blur: f32,
+    color: Rgba,
+    gradient: (f32, f32, f32, f32),
+    stops: Vec<(f32, Rgba)>,
+    stop_range: (f32, f32),
+    source: Rect,
+    texture: GLuint,
+}
+
+impl Quad {
+    fn new(mode: Mode, rect: Rect, radii: Corners<f32>, color: Rgba) -> Quad {
+        Quad {
+            mode,
+            rect,
+            radii,
+            widths: TRBL::all(0.0),
+            side: None,
+            dash: (0.0, 0.0),
+            shape: rect,
+            shape_radii: radii,
+            blur: 0.0,
+            color,
+            gradient: (0.0, 0.0, 0.0, 0.0),
+            stops: vec![],
+            stop_range: (std::f32::MIN, std::f32::MAX),
+            source: Rect::new(0.0, 0.0, 1.0, 1.0),
+            texture: 0,
+        }
+    }
+}
+
+struct Uniforms {
+    mode: GLint,
+    viewport: GLint,
+    rect: GLint,
+    radii: GLint,
+    widths: GLint,
+    side: GLint,
+    dash: GLint,
+    shape: GLint,
+    shape_radii: GLint,
+    blur: GLint,
+    color: GLint,
+    gradient: GLint,
+    stop_colors: GLint,
+    stop_positions: GLint,
+    stop_count: GLint,
+    stop_range: GLint,
+    source: GLint,
+}
+
 pub struct Renderer {
     shaderProgram: u32,
+    uniforms: Uniforms,
+    atlas: GlyphAtlas,
+    atlas_texture: u32,
+    /// Layers by nesting depth, kept between frames.
+    layers: Vec<Layer>,
     VBO: u32,
     VAO: u32,
     EBO: u32,
@@ -35,7 +263,7 @@ pub struct Renderer {
 impl Renderer {
     pub fn new() -> Renderer {
 
-        let (shaderProgram, VBO, VAO, EBO) = unsafe {
+        let (shaderProgram, uniforms, atlas_texture, VBO, VAO, EBO) = unsafe {
             // build and compile our shader program
             // ------------------------------------
             // vertex shader
@@ -56,7 +284,8 @@ impl Renderer {
 
             // fragment shader
             let fragmentShader = gl::CreateShader(gl::FRAGMENT_SHADER);
-            let c_str_frag = CString::new(FS_SRC.as_bytes()).unwrap();
+            let fs_src = FS_SRC.replace("MAX_GRADIENT_STOPS", &MAX_GRADIENT_STOPS.to_string());
+            let c_str_frag = CString::new(fs_src.as_bytes()).unwrap();
             gl::ShaderSource(fragmentShader, 1, &c_str_frag.as_ptr(), ptr::null());
             gl::CompileShader(fragmentShader);
             // check for shader compile errors
@@ -80,6 +309,42 @@ impl Renderer {
             gl::DeleteShader(vertexShader);
             gl::DeleteShader(fragmentShader);
 
+            let uniform = |name: &str| {
+                let c_str_name = CString::new(name).unwrap();
+                gl::GetUniformLocation(shaderProgram, c_str_name.as_ptr())
+            };
+            let uniforms = Uniforms {
+                mode: uniform("mode"),
+                viewport: uniform("viewport"),
+                rect: uniform("rect"),
+                radii: uniform("radii"),
+                widths: uniform("widths"),
+                side: uniform("side"),
+                dash: uniform("dash"),
+                shape: uniform("shape"),
+                shape_radii: uniform("shape_radii"),
+                blur: uniform("blur"),
+                color: uniform("color"),
+                gradient: uniform("gradient"),
+                stop_colors: uniform("stop_colors"),
+                stop_positions: uniform("stop_positions"),
+                stop_count: uniform("stop_count"),
+                stop_range: uniform("stop_range"),
+                source: uniform("source"),
+            };
+
+            // glyph atlas, glyphs are drawn at whole pixels so no filtering
+            // is needed
+            let mut atlas_texture = 0;
+            gl::GenTextures(1, &mut atlas_texture);
+            gl::BindTexture(gl::TEXTURE_2D, atlas_texture);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::R8 as GLint, ATLAS_SIZE as GLsizei, ATLAS_SIZE as GLsizei, 0, gl::RED, gl::UNSIGNED_BYTE, ptr::null());
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+
             // set up vertex data (and buffer(s)) and configure vertex attributes
             // ------------------------------------------------------------------
             // add a new set of vertices to form a second triangle (a total of 6 vertices); the vertex attribute configuration remains the same (still one 3-float position vector per vertex)
@@ -120,64 +385,361 @@ impl Renderer {
 
             gl::BindVertexArray(0);
 
-            (shaderProgram, VBO, VAO, EBO)
+            (shaderProgram, uniforms, atlas_texture, VBO, VAO, EBO)
         };
 
         Renderer {
             shaderProgram,
+            uniforms,
+            atlas: GlyphAtlas::new(ATLAS_SIZE, ATLAS_SIZE),
+            atlas_texture,
+            layers: vec![],
             VBO,
             VAO,
             EBO,
         }
     }
 
-    pub fn render(&mut self, size: Size, commands: Vec<Command>) {
-        if size.width < 1 || size.height < 1 {
+    /// Draws `commands` into a framebuffer of `size` device pixels, with the
+    /// images and fonts they refer to taken from `images` and `fonts`.
+    pub fn render(&mut self, size: Size, commands: Vec<Command>, images: &ImageRegistry, fonts: &Fonts) {
+        if size.width < 1.0 || size.height < 1.0 {
             return;
         }
 
+        // alpha is accumulated so that layers end up premultiplied
+        let mut target: GLint = 0;
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut target);
+            gl::Enable(gl::BLEND);
+            gl::BlendFuncSeparate(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+        }
+
+        // clip rects are kept in window coordinates, already translated
+        let mut translations: Vec<(f32, f32)> = vec![];
+        let mut clips: Vec<(f32, f32, f32, f32)> = vec![];
+        // opacity of every open group, groups that are fully opaque get no
+        // layer
+        let mut opacities: Vec<Option<f32>> = vec![];
+        let mut depth = 0;
+        let mut translation = (0.0, 0.0);
+
+        let translate = |rect: Rect, translation: (f32, f32)| Rect::new(rect.x + translation.0, rect.y + translation.1, rect.width, rect.height);
+
         for command in commands {
             match command {
-                Command::DrawRect(box_rect, box_color) => {
+                Command::PushTranslation(x, y) => {
+                    translations.push(translation);
+                    translation = (translation.0 + x, translation.1 + y);
+                },
+                Command::PopTranslation => {
+                    translation = translations.pop().unwrap_or((0.0, 0.0));
+                },
+                Command::PushOpacity(value) => {
+                    if value >= 1.0 {
+                        opacities.push(None);
+                        continue;
+                    }
+                    opacities.push(Some(value));
+                    self.bind_layer(size, depth);
+                    depth += 1;
+                    self.scissor(size, clips.last().cloned());
+                },
+                Command::PopOpacity => {
+                    let value = match opacities.pop() {
+                        Some(Some(value)) => value,
+                        _ => continue,
+                    };
+                    depth -= 1;
+                    let texture = self.layers[depth].texture;
                     unsafe {
-                        // gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
-
-                        gl::UseProgram(self.shaderProgram);
-                        gl::BindVertexArray(self.VAO);
-
-                        let color_string = CString::new("color").unwrap();
-                        let color_location = gl::GetUniformLocation(self.shaderProgram, color_string.as_ptr());
-                        
-                        let x = (box_rect.x as f32 / size.width as f32) * 2.0;
-                        let y = (box_rect.y as f32 / size.height as f32) * 2.0;
-                        let width = (box_rect.width as f32 / size.width as f32) * 2.0;
-                        let height = (box_rect.height as f32 / size.height as f32) * 2.0;
-
-                        let vertices: [f32; 8] = [
-                            -1.0 + x,         1.0 - y - height,
-                            -1.0 + x + width, 1.0 - y - height,
-                            -1.0 + x + width, 1.0 - y,
-                            -1.0 + x,         1.0 - y,
-                        ];
-
-                        gl::BindBuffer(gl::ARRAY_BUFFER, self.VBO);
-                        gl::BufferData(gl::ARRAY_BUFFER,
-                        (vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
-                        &vertices[0] as *const f32 as *const c_void,
-                        gl::STATIC_DRAW);
-
-                        gl::Uniform4f(
-                            color_location,
-                            box_color.r as f32,
-                            box_color.g as f32,
-                            box_color.b as f32,
-                            box_color.a as f32
+                        let framebuffer = if depth > 0 { self.layers[depth - 1].framebuffer } else { target as GLuint };
+                        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+                        gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+                    }
+                    let window = Rect::new(0.0, 0.0, size.width, size.height);
+                    let quad = Quad {
+                        texture,
+                        ..Quad::new(Mode::Layer, window, Corners::all(0.0), Rgba::new(1.0, 1.0, 1.0, value as f64))
+                    };
+                    self.draw(size, window, &quad);
+                    unsafe {
+                        gl::BlendFuncSeparate(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+                    }
+                },
+                Command::PushClip(clip_rect) => {
+                    let mut clip = (
+                        clip_rect.x + translation.0,
+                        clip_rect.y + translation.1,
+                        clip_rect.x + translation.0 + clip_rect.width,
+                        clip_rect.y + translation.1 + clip_rect.height,
+                    );
+                    if let Some(outer) = clips.last() {
+                        clip = (clip.0.max(outer.0), clip.1.max(outer.1), clip.2.min(outer.2), clip.3.min(outer.3));
+                    }
+                    clips.push(clip);
+                    self.scissor(size, clips.last().cloned());
+                },
+                Command::PopClip => {
+                    clips.pop();
+                    self.scissor(size, clips.last().cloned());
+                },
+                Command::DrawRect(rect, color) => {
+                    let quad = Quad::new(Mode::Fill, translate(rect, translation), Corners::all(0.0), color);
+                    self.draw(size, quad.rect, &quad);
+                },
+                Command::DrawRoundedRect(rect, radii, color) => {
+                    let quad = Quad::new(Mode::Fill, translate(rect, translation), radii, color);
+                    self.draw(size, quad.rect, &quad);
+                },
+                Command::DrawGradient(rect, radii, gradient) => {
+                    let color = Rgba::new(1.0, 1.0, 1.0, 1.0);
+                    let (x, y) = translation;
+                    let (mode, gradient, stops) = match gradient {
+                        Gradient::Linear { start, end, stops } => (Mode::LinearGradient, (start.0 + x, start.1 + y, end.0 + x, end.1 + y), stops),
+                        Gradient::Radial { center, radius, stops } => (Mode::RadialGradient, (center.0 + x, center.1 + y, radius, 0.0), stops),
+                    };
+                    // long gradients are drawn in parts, each covering the
+                    // positions between its first and last stop
+                    let chunks = stop_chunks(stops.len());
+                    for (ix, chunk) in chunks.iter().enumerate() {
+                        let from = if ix == 0 { std::f32::MIN } else { stops[chunk.start].0 };
+                        let to = if ix == chunks.len() - 1 { std::f32::MAX } else { stops[chunk.end - 1].0 };
+                        let quad = Quad {
+                            gradient,
+                            stops: stops[chunk.clone()].to_vec(),
+                            stop_range: (from, to),
+                            ..Quad::new(mode, translate(rect, translation), radii, color)
+                        };
+                        self.draw(size, quad.rect, &quad);
+                    }
+                },
+                Command::DrawImage { rect, radii, image, fit, scale } => {
+                    let image = match images.get(image) {
+                        Some(image) => image,
+                        None => continue,
+                    };
+                    let mode = if fit == ImageFit::Repeat { Mode::RepeatImage } else { Mode::Image };
+                    let rect = translate(rect, translation);
+                    let (width, height) = (image.size.width, image.size.height);
+                    for (destination, source) in image_rects(rect, image.size, fit, scale) {
+                        let quad = Quad {
+                            shape: destination,
+                            source: Rect::new(source.x / width, source.y / height, source.width / width, source.height / height),
+                            texture: image.texture,
+                            ..Quad::new(mode, rect, radii, Rgba::new(1.0, 1.0, 1.0, 1.0))
+                        };
+                        self.draw(size, destination, &quad);
+                    }
+                },
+                Command::DrawText(run) => {
+                    let color = run.color;
+                    let (x, y) = (run.origin.0 + translation.0, run.origin.1 + translation.1);
+                    let atlas_size = self.atlas.get_size();
+                    for glyph in run.glyphs.iter() {
+                        let atlas_glyph = match self.atlas.get_glyph(fonts, run.font, glyph.id, run.size) {
+                            Some(atlas_glyph) => atlas_glyph,
+                            None => continue,
+                        };
+                        self.upload_atlas();
+                        let source = atlas_glyph.rect;
+                        let destination = Rect::new(
+                            (x + glyph.x).round() + atlas_glyph.offset.0,
+                            y.round() + atlas_glyph.offset.1,
+                            source.width,
+                            source.height
                         );
-                        
-                        gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, ptr::null());
-                        gl::BindVertexArray(0);
+                        let quad = Quad {
+                            shape: destination,
+                            source: Rect::new(source.x / atlas_size.width, source.y / atlas_size.height, source.width / atlas_size.width, source.height / atlas_size.height),
+                            texture: self.atlas_texture,
+                            ..Quad::new(Mode::Text, destination, Corners::all(0.0), color)
+                        };
+                        self.draw(size, destination, &quad);
                     }
-                }
+                },
+                Command::DrawRoundedBorder { rect, radii, widths, side, color, dash } => {
+                    let quad = Quad {
+                        widths,
+                        side,
+                        dash: dash.unwrap_or((0.0, 0.0)),
+                        ..Quad::new(Mode::Ring, translate(rect, translation), radii, color)
+                    };
+                    self.draw(size, quad.rect, &quad);
+                },
+                Command::DrawShadow { shape, radii, blur, color } => {
+                    let shape = translate(shape, translation);
+                    let quad = Quad {
+                        blur,
+                        ..Quad::new(Mode::Shadow, shape, radii, color)
+                    };
+                    // the gaussian fades out after three standard deviations
+                    let extent = blur * 1.5;
+                    let bounds = Rect::new(shape.x - extent, shape.y - extent, shape.width + extent * 2.0, shape.height + extent * 2.0);
+                    self.draw(size, bounds, &quad);
+                },
+                Command::DrawInsetShadow { rect, radii, shape, shape_radii, blur, color } => {
+                    let quad = Quad {
+                        shape: translate(shape, translation),
+                        shape_radii,
+                        blur,
+                        ..Quad::new(Mode::InsetShadow, translate(rect, translation), radii, color)
+                    };
+                    self.draw(size, quad.rect, &quad);
+                },
+            }
+        }
+    }
+
+    /// Runs the fragment shader for `quad` over `bounds`, in window pixels.
+    fn draw(&self, size: Size, bounds: Rect, quad: &Quad) {
+        if bounds.width <= 0.0 || bounds.height <= 0.0 {
+            return;
+        }
+
+        let x = (bounds.x / size.width) * 2.0;
+        let y = (bounds.y / size.height) * 2.0;
+        let width = (bounds.width / size.width) * 2.0;
+        let height = (bounds.height / size.height) * 2.0;
+
+        let vertices: [f32; 8] = [
+            -1.0 + x,         1.0 - y - height,
+            -1.0 + x + width, 1.0 - y - height,
+            -1.0 + x + width, 1.0 - y,
+            -1.0 + x,         1.0 - y,
+        ];
+
+        let rect = |rect: Rect| (rect.x, rect.y, rect.width, rect.height);
+        let corners = |corners: Corners<f32>| (corners.top_left, corners.top_right, corners.bottom_right, corners.bottom_left);
+
+        unsafe {
+            gl::UseProgram(self.shaderProgram);
+            gl::BindVertexArray(self.VAO);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.VBO);
+            gl::BufferData(gl::ARRAY_BUFFER,
+            (vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+            &vertices[0] as *const f32 as *const c_void,
+            gl::STATIC_DRAW);
+
+            let uniforms = &self.uniforms;
+            let (rx, ry, rw, rh) = rect(quad.rect);
+            let (sx, sy, sw, sh) = rect(quad.shape);
+            let (r0, r1, r2, r3) = corners(quad.radii);
+            let (s0, s1, s2, s3) = corners(quad.shape_radii);
+            let (ux, uy, uw, uh) = rect(quad.source);
+            gl::Uniform1i(uniforms.mode, quad.mode as GLint);
+            gl::Uniform2f(uniforms.viewport, size.width, size.height);
+            gl::Uniform4f(uniforms.rect, rx, ry, rw, rh);
+            gl::Uniform4f(uniforms.radii, r0, r1, r2, r3);
+            gl::Uniform4f(uniforms.widths, quad.widths.top, quad.widths.right, quad.widths.bottom, quad.widths.left);
+            gl::Uniform1i(uniforms.side, quad.side.map_or(-1, |side| side as GLint));
+            gl::Uniform2f(uniforms.dash, quad.dash.0, quad.dash.1);
+            gl::Uniform4f(uniforms.shape, sx, sy, sw, sh);
+            gl::Uniform4f(uniforms.shape_radii, s0, s1, s2, s3);
+            gl::Uniform1f(uniforms.blur, quad.blur);
+            gl::Uniform4f(uniforms.source, ux, uy, uw, uh);
+            gl::BindTexture(gl::TEXTURE_2D, quad.texture);
+            gl::Uniform4f(
+                uniforms.color,
+                quad.color.r as f32,
+                quad.color.g as f32,
+                quad.color.b as f32,
+                quad.color.a as f32
+            );
+
+            let stops = &quad.stops[..quad.stops.len().min(MAX_GRADIENT_STOPS)];
+            gl::Uniform1i(uniforms.stop_count, stops.len() as GLint);
+            gl::Uniform2f(uniforms.stop_range, quad.stop_range.0, quad.stop_range.1);
+            if !stops.is_empty() {
+                let (g0, g1, g2, g3) = quad.gradient;
+                let positions: Vec<f32> = stops.iter().map(|&(position, _)| position).collect();
+                let colors: Vec<f32> = stops.iter()
+                    .flat_map(|&(_, color)| vec![color.r as f32, color.g as f32, color.b as f32, color.a as f32])
+                    .collect();
+                gl::Uniform4f(uniforms.gradient, g0, g1, g2, g3);
+                gl::Uniform1fv(uniforms.stop_positions, stops.len() as GLsizei, positions.as_ptr());
+                gl::Uniform4fv(uniforms.stop_colors, stops.len() as GLsizei, colors.as_ptr());
+            }
+
+            gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, ptr::null());
+            gl::BindVertexArray(0);
+        }
+    }
+
+    /// Makes the layer at `depth` the target of drawing and clears it,
+    /// creating it or resizing it to `size` first.
+    fn bind_layer(&mut self, size: Size, depth: usize) {
+        let (width, height) = (size.width as GLsizei, size.height as GLsizei);
+        unsafe {
+            if depth == self.layers.len() {
+                let (mut framebuffer, mut texture) = (0, 0);
+                gl::GenFramebuffers(1, &mut framebuffer);
+                gl::GenTextures(1, &mut texture);
+                gl::BindTexture(gl::TEXTURE_2D, texture);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
+                self.layers.push(Layer {
+                    framebuffer,
+                    texture,
+                    size: (0, 0),
+                });
+            }
+
+            let layer = &mut self.layers[depth];
+            if layer.size != (width, height) {
+                gl::BindTexture(gl::TEXTURE_2D, layer.texture);
+                gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA8 as GLint, width, height, 0, gl::RGBA, gl::UNSIGNED_BYTE, ptr::null());
+                gl::BindFramebuffer(gl::FRAMEBUFFER, layer.framebuffer);
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, layer.texture, 0);
+                layer.size = (width, height);
+            }
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, layer.framebuffer);
+            gl::Disable(gl::SCISSOR_TEST);
+            gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+    }
+
+    /// Copies the rows of the glyph atlas that changed into its texture.
+    fn upload_atlas(&mut self) {
+        if let Some((row, rows)) = self.atlas.take_dirty() {
+            let width = self.atlas.get_size().width as usize;
+            let pixels = &self.atlas.get_pixels()[row as usize * width..];
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_2D, self.atlas_texture);
+                gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+                gl::TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    0,
+                    row as GLint,
+                    width as GLsizei,
+                    rows as GLsizei,
+                    gl::RED,
+                    gl::UNSIGNED_BYTE,
+                    pixels.as_ptr() as *const c_void
+                );
+            }
+        }
+    }
+
+    /// Limits drawing to `clip`, given as left, top, right and bottom edge in
+    /// window coordinates, or lifts the limit.
+    fn scissor(&self, size: Size, clip: Option<(f32, f32, f32, f32)>) {
+        unsafe {
+            match clip {
+                Some((left, top, right, bottom)) => {
+                    gl::Enable(gl::SCISSOR_TEST);
+                    gl::Scissor(
+                        left as GLint,
+                        (size.height - bottom) as GLint,
+                        (right - left).max(0.0) as GLsizei,
+                        (bottom - top).max(0.0) as GLsizei,
+                    );
+                },
+                None => gl::Disable(gl::SCISSOR_TEST),
             }
         }
     }